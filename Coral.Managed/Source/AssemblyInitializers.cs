@@ -0,0 +1,155 @@
+using Coral.Managed.Interop;
+
+using System;
+using System.Collections.Generic;
+using System.Linq;
+using System.Reflection;
+using System.Runtime.InteropServices;
+
+namespace Coral.Managed;
+
+using static ManagedHost;
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct NativeInitializerError
+{
+	public NativeString Method;
+	public NativeString Message;
+}
+
+internal static class AssemblyInitializers
+{
+	[ThreadStatic] private static List<(string Method, string Message)>? s_LastErrors;
+
+	/// <summary>
+	/// Calls every static method in the assembly marked with the attribute InAttributeName, lowest priority first.
+	/// The priority is the attribute's Priority property, or its first int constructor argument, and 0 if it has neither.
+	/// Returns the number of initializers that failed, see GetLastInitializerErrors.
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static int RunAssemblyInitializers(int InAssemblyId, NativeString InAttributeName, IntPtr InArgument, Bool32 InArgumentIsObject)
+	{
+		var errors = s_LastErrors ??= new();
+		errors.Clear();
+
+		try
+		{
+			string? attributeName = InAttributeName;
+
+			if (string.IsNullOrEmpty(attributeName))
+				return 0;
+
+			if (!AssemblyLoader.TryGetAssembly(InAssemblyId, out var assembly) || assembly == null)
+			{
+				LogMessage($"Couldn't run initializers for assembly '{InAssemblyId}', assembly not found.", MessageLevel.Error);
+				return 0;
+			}
+
+			var initializers = new List<(MethodInfo Method, int Priority)>();
+
+			foreach (var type in assembly.GetTypes())
+			{
+				foreach (var method in type.GetMethods(BindingFlags.Public | BindingFlags.NonPublic | BindingFlags.Static | BindingFlags.DeclaredOnly))
+				{
+					var attribute = method.CustomAttributes.FirstOrDefault(attribute => attribute.AttributeType.FullName == attributeName);
+
+					if (attribute != null)
+						initializers.Add((method, GetPriority(attribute)));
+				}
+			}
+
+			// NOTE: OrderBy is stable, initializers with the same priority run in the order they were found
+			foreach (var (method, _) in initializers.OrderBy(initializer => initializer.Priority))
+			{
+				try
+				{
+					method.Invoke(null, GetArguments(method, InArgument, InArgumentIsObject));
+				}
+				catch (TargetInvocationException ex) when (ex.InnerException != null)
+				{
+					errors.Add((GetMethodName(method), ex.InnerException.ToString()));
+				}
+				catch (Exception ex)
+				{
+					errors.Add((GetMethodName(method), ex.Message));
+				}
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+
+		return errors.Count;
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetLastInitializerErrors(NativeInitializerError* OutErrors, int* OutErrorCount)
+	{
+		try
+		{
+			var errors = s_LastErrors;
+			*OutErrorCount = errors?.Count ?? 0;
+
+			if (OutErrors == null || errors == null)
+				return;
+
+			for (int i = 0; i < errors.Count; i++)
+			{
+				OutErrors[i] = new NativeInitializerError
+				{
+					Method = errors[i].Method,
+					Message = errors[i].Message
+				};
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	private static int GetPriority(CustomAttributeData InAttribute)
+	{
+		foreach (var argument in InAttribute.NamedArguments)
+		{
+			if (argument.MemberName == "Priority" && argument.TypedValue.Value is int priority)
+				return priority;
+		}
+
+		foreach (var argument in InAttribute.ConstructorArguments)
+		{
+			if (argument.Value is int priority)
+				return priority;
+		}
+
+		return 0;
+	}
+
+	/// <summary>
+	/// Initializers take either no parameters, or one that receives the host's argument
+	/// (the object itself, or the pointer as an IntPtr or pointer type)
+	/// </summary>
+	private static unsafe object?[]? GetArguments(MethodInfo InMethod, IntPtr InArgument, bool InArgumentIsObject)
+	{
+		var parameters = InMethod.GetParameters();
+
+		if (parameters.Length == 0)
+			return null;
+
+		if (parameters.Length > 1)
+			throw new ArgumentException($"Initializers can take at most one parameter, it takes {parameters.Length}.");
+
+		if (InArgumentIsObject)
+			return new[] { InArgument == IntPtr.Zero ? null : GCHandle.FromIntPtr(InArgument).Target };
+
+		var parameterType = parameters[0].ParameterType;
+
+		if (parameterType.IsPointer)
+			return new[] { Pointer.Box(InArgument.ToPointer(), parameterType) };
+
+		return new object?[] { InArgument };
+	}
+
+	private static string GetMethodName(MethodInfo InMethod) => $"{InMethod.DeclaringType?.FullName}.{InMethod.Name}";
+}
@@ -1,12 +1,14 @@
 ﻿using Coral.Managed.Interop;
 
 using System;
+using System.Collections.Concurrent;
 using System.Collections.Generic;
 using System.IO;
 using System.IO.MemoryMappedFiles;
 using System.Reflection;
 using System.Runtime.InteropServices;
 using System.Runtime.Loader;
+using System.Threading;
 
 namespace Coral.Managed;
 
@@ -14,18 +16,41 @@ using static ManagedHost;
 
 public enum AssemblyLoadStatus
 {
-	Success, FileNotFound, FileLoadFailure, InvalidFilePath, InvalidAssembly, UnknownError
+	Success, FileNotFound, FileLoadFailure, InvalidFilePath, InvalidAssembly, UnknownError, CompilerNotFound, CompilationFailed, VerificationFailed, SandboxViolation
+}
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct AssemblyLoadContextInfo
+{
+	public int ContextId;
+	public NativeString Name;
+	public int AssembliesOffset;
+	public int AssemblyCount;
+	public int TypeCount;
+	public int OutstandingHandleCount;
+	public Bool32 UnloadPending;
 }
 
 public static class AssemblyLoader
 {
 	private static readonly Dictionary<Type, AssemblyLoadStatus> s_AssemblyLoadErrorLookup = new();
-	private static readonly Dictionary<int, AssemblyLoadContext?> s_AssemblyContexts = new();
-	private static readonly Dictionary<int, Assembly> s_AssemblyCache = new();
+	// NOTE: Concurrent since assemblies can be loaded from worker threads
+	private static readonly ConcurrentDictionary<int, CoralLoadContext?> s_AssemblyContexts = new();
+	private static readonly ConcurrentDictionary<int, Assembly> s_AssemblyCache = new();
+	private static readonly ConcurrentDictionary<Assembly, int> s_AssemblyIds = new();
+	// NOTE: Both are locked on, since objects can be created and contexts unloaded while a worker thread loads an assembly
 	private static readonly Dictionary<int, List<GCHandle>> s_AllocatedHandles = new();
-	private static AssemblyLoadStatus s_LastLoadStatus = AssemblyLoadStatus.Success;
+	private static readonly List<(int ContextId, WeakReference Context)> s_UnloadingContexts = new();
+	// NOTE: Per thread, so a load on a worker thread doesn't overwrite the status of one on the main thread
+	[ThreadStatic] private static AssemblyLoadStatus s_LastLoadStatus;
+	[ThreadStatic] private static string? s_LastVerificationFailure;
+	[ThreadStatic] private static IReadOnlyList<SandboxFinding>? s_LastSandboxFindings;
 
 	private static readonly AssemblyLoadContext? s_CoralAssemblyLoadContext;
+	private static CoralLoadContext? s_SharedAssemblyLoadContext;
+	private static int s_SharedAssemblyLoadContextId;
+	private static int s_NextAssemblyId;
+	private static int s_NextContextId;
 
 	static AssemblyLoader()
 	{
@@ -44,10 +69,27 @@ public static class AssemblyLoader
 	private static void CacheCoralAssemblies()
 	{
 		foreach (var assembly in s_CoralAssemblyLoadContext!.Assemblies)
-		{
-			int assemblyId = assembly.GetName().Name!.GetHashCode();
-			s_AssemblyCache.Add(assemblyId, assembly);
-		}
+			GetAssemblyId(assembly);
+	}
+
+	/// <summary>
+	/// Ids are allocated the first time an assembly is seen, so they're unique for every load, even of the same assembly
+	/// (e.g two versions of it in different contexts). They're never reused.
+	/// </summary>
+	internal static int GetAssemblyId(Assembly InAssembly)
+	{
+		int assemblyId = s_AssemblyIds.GetOrAdd(InAssembly, _ => Interlocked.Increment(ref s_NextAssemblyId));
+		s_AssemblyCache.TryAdd(assemblyId, InAssembly);
+		return assemblyId;
+	}
+
+	private static void ForgetAssembly(Assembly InAssembly)
+	{
+		if (!s_AssemblyIds.TryRemove(InAssembly, out int assemblyId))
+			return;
+
+		s_AssemblyCache.TryRemove(assemblyId, out _);
+		Interop.InternalCallPauses.Forget(assemblyId);
 	}
 
 	internal static bool TryGetAssembly(int InAssemblyId, out Assembly? OutAssembly)
@@ -59,21 +101,17 @@ public static class AssemblyLoader
 	{
 		try
 		{
-			int assemblyId = InAssemblyName.Name!.GetHashCode();
-
-			if (s_AssemblyCache.TryGetValue(assemblyId, out var cachedAssembly))
-			{
-				return cachedAssembly;
-			}
-
 			foreach (var loadContext in AssemblyLoadContext.All)
 			{
+				// NOTE: Other script contexts can have their own version of the assembly, which must not leak into this one
+				if (loadContext is CoralLoadContext && loadContext != InAssemblyLoadContext && loadContext != s_SharedAssemblyLoadContext)
+					continue;
+
 				foreach (var assembly in loadContext.Assemblies)
 				{
 					if (assembly.GetName().Name != InAssemblyName.Name)
 						continue;
 
-					s_AssemblyCache.Add(assemblyId, assembly);
 					return assembly;
 				}
 			}
@@ -86,6 +124,47 @@ public static class AssemblyLoader
 		return null;
 	}
 
+	internal static Assembly? FindSharedAssembly(AssemblyName InAssemblyName)
+	{
+		if (s_SharedAssemblyLoadContext == null)
+			return null;
+
+		foreach (var assembly in s_SharedAssemblyLoadContext.Assemblies)
+		{
+			if (AssemblyName.ReferenceMatchesDefinition(InAssemblyName, assembly.GetName()))
+				return assembly;
+		}
+
+		return null;
+	}
+
+	/// <summary>
+	/// The context shared assemblies are loaded into, created on first use. Every other context
+	/// resolves against it before looking anywhere else. It's never unloaded.
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static int GetSharedAssemblyLoadContextId()
+	{
+		try
+		{
+			if (s_SharedAssemblyLoadContext != null)
+				return s_SharedAssemblyLoadContextId;
+
+			const string name = "Coral.Shared";
+			s_SharedAssemblyLoadContext = new CoralLoadContext(name, false);
+			s_SharedAssemblyLoadContext.Resolving += ResolveAssembly;
+
+			s_SharedAssemblyLoadContextId = Interlocked.Increment(ref s_NextContextId);
+			s_AssemblyContexts[s_SharedAssemblyLoadContextId] = s_SharedAssemblyLoadContext;
+			return s_SharedAssemblyLoadContextId;
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+			return -1;
+		}
+	}
+
 	[UnmanagedCallersOnly]
 	internal static int CreateAssemblyLoadContext(NativeString InName)
 	{
@@ -94,20 +173,17 @@ public static class AssemblyLoader
 		if (name == null)
 			return -1;
 
-		var alc = new AssemblyLoadContext(name, true);
+		var alc = new CoralLoadContext(name);
 		alc.Resolving += ResolveAssembly;
+		alc.Resolving += (_, assemblyName) => alc.ResolveExternalAssembly(assemblyName);
 		alc.Unloading += ctx =>
 		{
 			foreach (var assembly in ctx.Assemblies)
-			{
-				var assemblyName = assembly.GetName();
-				int assemblyId = assemblyName.Name!.GetHashCode();
-				s_AssemblyCache.Remove(assemblyId);
-			}
+				ForgetAssembly(assembly);
 		};
 
-		int contextId = name.GetHashCode();
-		s_AssemblyContexts.Add(contextId, alc);
+		int contextId = Interlocked.Increment(ref s_NextContextId);
+		s_AssemblyContexts[contextId] = alc;
 		return contextId;
 	}
 
@@ -126,40 +202,180 @@ public static class AssemblyLoader
 			return;
 		}
 
-		foreach (var assembly in alc.Assemblies)
+		if (alc == s_SharedAssemblyLoadContext)
 		{
-			var assemblyName = assembly.GetName();
-			int assemblyId = assemblyName.Name!.GetHashCode();
+			LogMessage("The shared AssemblyLoadContext can't be unloaded.", MessageLevel.Warning);
+			return;
+		}
 
-			if (!s_AllocatedHandles.TryGetValue(assemblyId, out var handles))
+		lock (s_AllocatedHandles)
+		{
+			foreach (var assembly in alc.Assemblies)
 			{
-				continue;
-			}
+				var assemblyName = assembly.GetName();
 
-			foreach (var handle in handles)
-			{
-				if (!handle.IsAllocated || handle.Target == null)
+				if (!s_AssemblyIds.TryGetValue(assembly, out int assemblyId) || !s_AllocatedHandles.TryGetValue(assemblyId, out var handles))
 				{
 					continue;
 				}
 
-				LogMessage($"Found unfreed object '{handle.Target}' from assembly '{assemblyName}'. Deallocating.", MessageLevel.Warning);
-				handle.Free();
-			}
+				foreach (var handle in handles)
+				{
+					if (!handle.IsAllocated || handle.Target == null)
+					{
+						continue;
+					}
+
+					LogMessage($"Found unfreed object '{handle.Target}' from assembly '{assemblyName}'. Deallocating.", MessageLevel.Warning);
+					handle.Free();
+				}
 
-			s_AllocatedHandles.Remove(assemblyId);
+				s_AllocatedHandles.Remove(assemblyId);
+			}
 		}
 
-		ManagedObject.s_CachedMethods.Clear();
+		// NOTE: Only the entries of this context, the ones of other contexts (and the shared one) are still in use
+		foreach (var (methodKey, methodInfo) in ManagedObject.s_CachedMethods)
+		{
+			if (IsOwnedBy(methodInfo, alc))
+				ManagedObject.s_CachedMethods.TryRemove(methodKey, out _);
+		}
 
-		TypeInterface.s_CachedTypes.Clear();
-		TypeInterface.s_CachedMethods.Clear();
-		TypeInterface.s_CachedFields.Clear();
-		TypeInterface.s_CachedProperties.Clear();
-		TypeInterface.s_CachedAttributes.Clear();
+		TypeInterface.s_CachedTypes.RemoveWhere(type => IsOwnedBy(type, alc));
+		TypeInterface.s_CachedMethods.RemoveWhere(method => IsOwnedBy(method, alc));
+		TypeInterface.s_CachedFields.RemoveWhere(field => IsOwnedBy(field.DeclaringType, alc) || IsOwnedBy(field.FieldType, alc));
+		TypeInterface.s_CachedProperties.RemoveWhere(property => IsOwnedBy(property.DeclaringType, alc) || IsOwnedBy(property.PropertyType, alc));
+		TypeInterface.s_CachedAttributes.RemoveWhere(attribute => IsOwnedBy(attribute.GetType(), alc));
 
-		s_AssemblyContexts.Remove(InContextId);
+		s_AssemblyContexts.TryRemove(InContextId, out _);
+		alc.ClearResolvers();
 		alc.Unload();
+
+		// NOTE: The context is only gone once the GC collected it, until then it's reported as pending unload
+		lock (s_UnloadingContexts)
+			s_UnloadingContexts.Add((InContextId, new WeakReference(alc)));
+	}
+
+	/// <summary>
+	/// Whether InType, or a type it's constructed from (e.g the element type of an array or a generic argument), was loaded into InContext
+	/// </summary>
+	private static bool IsOwnedBy(Type? InType, AssemblyLoadContext InContext)
+	{
+		if (InType == null)
+			return false;
+
+		if (InType.HasElementType)
+			return IsOwnedBy(InType.GetElementType(), InContext);
+
+		if (AssemblyLoadContext.GetLoadContext(InType.Assembly) == InContext)
+			return true;
+
+		if (!InType.IsConstructedGenericType)
+			return false;
+
+		foreach (var argument in InType.GenericTypeArguments)
+		{
+			if (IsOwnedBy(argument, InContext))
+				return true;
+		}
+
+		return false;
+	}
+
+	private static bool IsOwnedBy(MethodInfo InMethod, AssemblyLoadContext InContext)
+	{
+		if (IsOwnedBy(InMethod.DeclaringType, InContext) || IsOwnedBy(InMethod.ReturnType, InContext))
+			return true;
+
+		foreach (var parameter in InMethod.GetParameters())
+		{
+			if (IsOwnedBy(parameter.ParameterType, InContext))
+				return true;
+		}
+
+		if (!InMethod.IsGenericMethod)
+			return false;
+
+		foreach (var argument in InMethod.GetGenericArguments())
+		{
+			if (IsOwnedBy(argument, InContext))
+				return true;
+		}
+
+		return false;
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void AddProbingDirectory(int InContextId, NativeString InDirectory)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to add probing directory '{InDirectory}', couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				return;
+			}
+
+			string? directory = InDirectory;
+
+			if (string.IsNullOrEmpty(directory))
+			{
+				LogMessage($"Failed to add probing directory to AssemblyLoadContext '{InContextId}', directory was empty.", MessageLevel.Error);
+				return;
+			}
+
+			alc.AddProbingDirectory(directory);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void SetDependencyManifest(int InContextId, NativeString InComponentAssemblyPath)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to use dependency manifest of '{InComponentAssemblyPath}', couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				return;
+			}
+
+			string? componentAssemblyPath = InComponentAssemblyPath;
+
+			if (string.IsNullOrEmpty(componentAssemblyPath))
+			{
+				LogMessage($"Failed to use dependency manifest for AssemblyLoadContext '{InContextId}', path was empty.", MessageLevel.Error);
+				return;
+			}
+
+			alc.SetDependencyResolver(componentAssemblyPath);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void SetAssemblyResolver(int InContextId, delegate*<IntPtr, NativeString, NativeString, AssemblyResolution*, Bool32> InAssemblyResolver, delegate*<IntPtr, NativeString, NativeString*, Bool32> InUnmanagedResolver, IntPtr InUserData)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to set assembly resolver, couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				return;
+			}
+
+			alc.SetResolvers(InAssemblyResolver, InUnmanagedResolver, InUserData);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
 	}
 
 	[UnmanagedCallersOnly]
@@ -196,19 +412,38 @@ public static class AssemblyLoader
 
 			Assembly? assembly = null;
 
-			using (var file = MemoryMappedFile.CreateFromFile(InAssemblyFilePath!))
+			if (alc.HasVerificationPolicy)
+			{
+				// NOTE: Memory mapped views are padded to the page size, which would change the hash of the assembly
+				using var stream = File.OpenRead(InAssemblyFilePath!);
+				assembly = alc.LoadFromStreamVerified(stream);
+			}
+			else
 			{
+				using var file = MemoryMappedFile.CreateFromFile(InAssemblyFilePath!);
 				using var stream = file.CreateViewStream();
 				assembly = alc.LoadFromStream(stream);
 			}
 
 			LogMessage($"Loading assembly '{InAssemblyFilePath}'", MessageLevel.Info);
-			var assemblyName = assembly.GetName();
-			int assemblyId = assemblyName.Name!.GetHashCode();
-			s_AssemblyCache.Add(assemblyId, assembly);
+			int assemblyId = GetAssemblyId(assembly);
 			s_LastLoadStatus = AssemblyLoadStatus.Success;
 			return assemblyId;
 		}
+		catch (SandboxViolationException ex)
+		{
+			LogMessage($"Assembly violates the sandbox policy: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.SandboxViolation;
+			s_LastSandboxFindings = ex.Findings;
+			return -1;
+		}
+		catch (AssemblyVerificationException ex)
+		{
+			LogMessage($"Assembly failed verification: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.VerificationFailed;
+			s_LastVerificationFailure = ex.Message;
+			return -1;
+		}
 		catch (Exception ex)
 		{
 			s_AssemblyLoadErrorLookup.TryGetValue(ex.GetType(), out s_LastLoadStatus);
@@ -218,7 +453,18 @@ public static class AssemblyLoader
 	}
 
 	[UnmanagedCallersOnly]
-	internal static unsafe int LoadAssemblyFromMemory(int InContextId, byte* data, long dataLength)
+	internal static unsafe int LoadAssemblyFromMemory(int InContextId, byte* InAssemblyData, long InAssemblyDataLength)
+	{
+		return LoadAssemblyFromMemoryInternal(InContextId, InAssemblyData, InAssemblyDataLength, null, 0);
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe int LoadAssemblyFromMemoryWithSymbols(int InContextId, byte* InAssemblyData, long InAssemblyDataLength, byte* InSymbolsData, long InSymbolsDataLength)
+	{
+		return LoadAssemblyFromMemoryInternal(InContextId, InAssemblyData, InAssemblyDataLength, InSymbolsData, InSymbolsDataLength);
+	}
+
+	private static unsafe int LoadAssemblyFromMemoryInternal(int InContextId, byte* InAssemblyData, long InAssemblyDataLength, byte* InSymbolsData, long InSymbolsDataLength)
 	{
 		try
 		{
@@ -238,18 +484,115 @@ public static class AssemblyLoader
 
 			Assembly? assembly = null;
 
-			using (var stream = new UnmanagedMemoryStream(data, dataLength))
+			using (var stream = new UnmanagedMemoryStream(InAssemblyData, InAssemblyDataLength))
 			{
-				assembly = alc.LoadFromStream(stream);
+				if (InSymbolsData != null && InSymbolsDataLength > 0)
+				{
+					using var symbolsStream = new UnmanagedMemoryStream(InSymbolsData, InSymbolsDataLength);
+					assembly = alc.LoadFromStreamVerified(stream, symbolsStream);
+				}
+				else
+				{
+					assembly = alc.LoadFromStreamVerified(stream);
+				}
 			}
 
 			LogMessage($"Loading assembly '{assembly.FullName}'", MessageLevel.Info);
-			var assemblyName = assembly.GetName();
-			int assemblyId = assemblyName.Name!.GetHashCode();
-			s_AssemblyCache.Add(assemblyId, assembly);
+			int assemblyId = GetAssemblyId(assembly);
+			s_LastLoadStatus = AssemblyLoadStatus.Success;
+			return assemblyId;
+		}
+		catch (SandboxViolationException ex)
+		{
+			LogMessage($"Assembly violates the sandbox policy: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.SandboxViolation;
+			s_LastSandboxFindings = ex.Findings;
+			return -1;
+		}
+		catch (AssemblyVerificationException ex)
+		{
+			LogMessage($"Assembly failed verification: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.VerificationFailed;
+			s_LastVerificationFailure = ex.Message;
+			return -1;
+		}
+		catch (Exception ex)
+		{
+			s_AssemblyLoadErrorLookup.TryGetValue(ex.GetType(), out s_LastLoadStatus);
+			HandleException(ex);
+			return -1;
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe int CompileAndLoadAssembly(int InContextId, NativeString InAssemblyName, NativeString* InSourcePaths, NativeString* InSourceCode, int InSourceCount, NativeString* InReferences, int InReferenceCount)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to compile assembly '{InAssemblyName}', couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				s_LastLoadStatus = AssemblyLoadStatus.UnknownError;
+				return -1;
+			}
+
+			string? assemblyName = InAssemblyName;
+
+			if (string.IsNullOrEmpty(assemblyName))
+			{
+				LogMessage("Failed to compile assembly, no assembly name was given.", MessageLevel.Error);
+				s_LastLoadStatus = AssemblyLoadStatus.UnknownError;
+				return -1;
+			}
+
+			var sources = new List<(string Path, string Code)>(InSourceCount);
+			for (int i = 0; i < InSourceCount; i++)
+				sources.Add(((string?)InSourcePaths[i] ?? "", (string?)InSourceCode[i] ?? ""));
+
+			var references = new List<string>(InReferenceCount);
+			for (int i = 0; i < InReferenceCount; i++)
+			{
+				string? reference = InReferences[i];
+
+				if (!string.IsNullOrEmpty(reference))
+					references.Add(reference);
+			}
+
+			var compiled = RuntimeCompiler.Compile(assemblyName, sources, references, out bool compilerFound);
+
+			if (compiled == null)
+			{
+				s_LastLoadStatus = compilerFound ? AssemblyLoadStatus.CompilationFailed : AssemblyLoadStatus.CompilerNotFound;
+				return -1;
+			}
+
+			Assembly? assembly = null;
+
+			using (var stream = new MemoryStream(compiled.Value.Assembly))
+			{
+				using var symbolsStream = new MemoryStream(compiled.Value.Symbols);
+				assembly = alc.LoadFromStreamVerified(stream, symbolsStream);
+			}
+
+			LogMessage($"Loading compiled assembly '{assembly.FullName}'", MessageLevel.Info);
+			int assemblyId = GetAssemblyId(assembly);
 			s_LastLoadStatus = AssemblyLoadStatus.Success;
 			return assemblyId;
 		}
+		catch (SandboxViolationException ex)
+		{
+			LogMessage($"Assembly violates the sandbox policy: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.SandboxViolation;
+			s_LastSandboxFindings = ex.Findings;
+			return -1;
+		}
+		catch (AssemblyVerificationException ex)
+		{
+			LogMessage($"Assembly failed verification: {ex.Message}", MessageLevel.Error);
+			s_LastLoadStatus = AssemblyLoadStatus.VerificationFailed;
+			s_LastVerificationFailure = ex.Message;
+			return -1;
+		}
 		catch (Exception ex)
 		{
 			s_AssemblyLoadErrorLookup.TryGetValue(ex.GetType(), out s_LastLoadStatus);
@@ -261,6 +604,115 @@ public static class AssemblyLoader
 	[UnmanagedCallersOnly]
 	internal static AssemblyLoadStatus GetLastLoadStatus() => s_LastLoadStatus;
 
+	[UnmanagedCallersOnly]
+	internal static NativeString GetLastVerificationFailure() => s_LastVerificationFailure;
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetLastSandboxFindings(NativeSandboxFinding* OutFindings, int* OutFindingCount)
+	{
+		try
+		{
+			var findings = s_LastSandboxFindings ?? Array.Empty<SandboxFinding>();
+			*OutFindingCount = findings.Count;
+
+			if (OutFindings == null)
+				return;
+
+			for (int i = 0; i < findings.Count; i++)
+			{
+				var finding = findings[i];
+
+				OutFindings[i] = new NativeSandboxFinding
+				{
+					TypeName = finding.TypeName,
+					MethodName = finding.MethodName,
+					Reference = finding.Reference
+				};
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void SetSandboxPolicy(int InContextId, NativeString* InDenied, int InDeniedCount, NativeString* InAllowed, int InAllowedCount, Bool32 InDenyPInvoke)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to set sandbox policy, couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				return;
+			}
+
+			static List<string> ToList(NativeString* InValues, int InCount)
+			{
+				var values = new List<string>(InCount);
+				for (int i = 0; i < InCount; i++)
+				{
+					string? value = InValues[i];
+
+					if (!string.IsNullOrEmpty(value))
+						values.Add(value);
+				}
+
+				return values;
+			}
+
+			var denied = ToList(InDenied, InDeniedCount);
+
+			if (denied.Count == 0 && !InDenyPInvoke)
+			{
+				alc.SetSandboxPolicy(null);
+				return;
+			}
+
+			alc.SetSandboxPolicy(new SandboxPolicy(denied, ToList(InAllowed, InAllowedCount), InDenyPInvoke));
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void SetVerificationPolicy(int InContextId, NativeString* InAllowedHashes, int InAllowedHashCount, NativeString* InRequiredPublicKeyTokens, int InRequiredPublicKeyTokenCount, delegate*<IntPtr, byte*, long, NativeString*, Bool32> InVerifier, IntPtr InUserData)
+	{
+		try
+		{
+			if (!s_AssemblyContexts.TryGetValue(InContextId, out var alc) || alc == null)
+			{
+				LogMessage($"Failed to set verification policy, couldn't find AssemblyLoadContext with id {InContextId}.", MessageLevel.Error);
+				return;
+			}
+
+			static HashSet<string>? ToSet(NativeString* InValues, int InCount)
+			{
+				if (InCount == 0)
+					return null;
+
+				var values = new HashSet<string>();
+				for (int i = 0; i < InCount; i++)
+				{
+					string? value = InValues[i];
+
+					if (value != null)
+						values.Add(value.ToLowerInvariant());
+				}
+
+				return values;
+			}
+
+			alc.SetVerificationPolicy(ToSet(InAllowedHashes, InAllowedHashCount), ToSet(InRequiredPublicKeyTokens, InRequiredPublicKeyTokenCount), InVerifier, InUserData);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
 	[UnmanagedCallersOnly]
 	internal static NativeString GetAssemblyName(int InAssemblyId)
 	{
@@ -274,18 +726,192 @@ public static class AssemblyLoader
 		return assemblyName.Name;
 	}
 
+	[UnmanagedCallersOnly]
+	internal static NativeString GetAssemblyVersion(int InAssemblyId)
+	{
+		if (!s_AssemblyCache.TryGetValue(InAssemblyId, out var assembly))
+		{
+			LogMessage($"Couldn't get assembly version for assembly '{InAssemblyId}', assembly not in dictionary.", MessageLevel.Error);
+			return "";
+		}
+
+		var assemblyName = assembly.GetName();
+		return assemblyName.Version?.ToString() ?? "";
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetAssemblyResourceNames(int InAssemblyId, NativeString* OutNames, int* OutNameCount)
+	{
+		try
+		{
+			if (!s_AssemblyCache.TryGetValue(InAssemblyId, out var assembly))
+			{
+				LogMessage($"Couldn't get resource names for assembly '{InAssemblyId}', assembly not in dictionary.", MessageLevel.Error);
+				*OutNameCount = 0;
+				return;
+			}
+
+			var names = assembly.GetManifestResourceNames();
+			*OutNameCount = names.Length;
+
+			if (OutNames == null)
+				return;
+
+			for (int i = 0; i < names.Length; i++)
+				OutNames[i] = names[i];
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	/// <summary>
+	/// OutDataLength is always set to the size of the resource, the contents are only copied if OutData isn't null
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static unsafe Bool32 ReadAssemblyResource(int InAssemblyId, NativeString InName, byte* OutData, long* OutDataLength)
+	{
+		try
+		{
+			if (!s_AssemblyCache.TryGetValue(InAssemblyId, out var assembly))
+			{
+				LogMessage($"Couldn't read resource '{InName}' from assembly '{InAssemblyId}', assembly not in dictionary.", MessageLevel.Error);
+				return false;
+			}
+
+			string? name = InName;
+
+			if (name == null)
+				return false;
+
+			using var stream = assembly.GetManifestResourceStream(name);
+
+			if (stream == null)
+				return false;
+
+			*OutDataLength = stream.Length;
+
+			if (OutData == null)
+				return true;
+
+			using var target = new UnmanagedMemoryStream(OutData, 0, stream.Length, FileAccess.Write);
+			stream.CopyTo(target);
+			return true;
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+			return false;
+		}
+	}
+
+	/// <summary>
+	/// Writes at most InContextCapacity contexts and InAssemblyNameCapacity assembly names, but always returns the real counts,
+	/// since contexts and assemblies can be added by other threads between two calls
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetAssemblyLoadContexts(AssemblyLoadContextInfo* OutContexts, int InContextCapacity, int* OutContextCount, NativeString* OutAssemblyNames, int InAssemblyNameCapacity, int* OutAssemblyNameCount)
+	{
+		try
+		{
+			var contexts = new List<(int ContextId, AssemblyLoadContext Context, bool UnloadPending)>();
+
+			foreach (var (contextId, alc) in s_AssemblyContexts)
+			{
+				if (alc != null)
+					contexts.Add((contextId, alc, false));
+			}
+
+			lock (s_UnloadingContexts)
+			{
+				s_UnloadingContexts.RemoveAll(context => !context.Context.IsAlive);
+
+				foreach (var (contextId, weakContext) in s_UnloadingContexts)
+				{
+					if (weakContext.Target is AssemblyLoadContext alc)
+						contexts.Add((contextId, alc, true));
+				}
+			}
+
+			int assemblyNameCount = 0;
+
+			for (int i = 0; i < contexts.Count; i++)
+			{
+				var (contextId, alc, unloadPending) = contexts[i];
+				var info = new AssemblyLoadContextInfo
+				{
+					ContextId = contextId,
+					AssembliesOffset = assemblyNameCount,
+					UnloadPending = unloadPending
+				};
+
+				foreach (var assembly in alc.Assemblies)
+				{
+					if (OutAssemblyNames != null && assemblyNameCount < InAssemblyNameCapacity)
+						OutAssemblyNames[assemblyNameCount] = assembly.GetName().Name;
+
+					assemblyNameCount++;
+
+					if (OutContexts == null || i >= InContextCapacity)
+						continue;
+
+					info.TypeCount += assembly.GetTypes().Length;
+
+					if (!s_AssemblyIds.TryGetValue(assembly, out int assemblyId))
+						continue;
+
+					lock (s_AllocatedHandles)
+					{
+						if (s_AllocatedHandles.TryGetValue(assemblyId, out var handles))
+							info.OutstandingHandleCount += handles.Count;
+					}
+				}
+
+				info.AssemblyCount = assemblyNameCount - info.AssembliesOffset;
+
+				if (OutContexts != null && i < InContextCapacity)
+				{
+					info.Name = alc.Name;
+					OutContexts[i] = info;
+				}
+			}
+
+			*OutContextCount = contexts.Count;
+			*OutAssemblyNameCount = assemblyNameCount;
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
 	internal static void RegisterHandle(Assembly InAssembly, GCHandle InHandle)
 	{
-		var assemblyName = InAssembly.GetName();
-		int assemblyId = assemblyName.Name!.GetHashCode();
+		int assemblyId = GetAssemblyId(InAssembly);
 
-		if (!s_AllocatedHandles.TryGetValue(assemblyId, out var handles))
+		lock (s_AllocatedHandles)
 		{
-			s_AllocatedHandles.Add(assemblyId, new List<GCHandle>());
-			handles = s_AllocatedHandles[assemblyId];
+			if (!s_AllocatedHandles.TryGetValue(assemblyId, out var handles))
+			{
+				s_AllocatedHandles.Add(assemblyId, new List<GCHandle>());
+				handles = s_AllocatedHandles[assemblyId];
+			}
+
+			handles.Add(InHandle);
 		}
+	}
 
-		handles.Add(InHandle);
+	internal static void UnregisterHandle(GCHandle InHandle)
+	{
+		lock (s_AllocatedHandles)
+		{
+			foreach (var handles in s_AllocatedHandles.Values)
+			{
+				if (handles.Remove(InHandle))
+					return;
+			}
+		}
 	}
 
 }
@@ -0,0 +1,20 @@
+using System;
+
+namespace Coral.Managed;
+
+public class AssemblyVerificationException : Exception
+{
+	public AssemblyVerificationException()
+	{
+	}
+
+	public AssemblyVerificationException(string message)
+		: base(message)
+	{
+	}
+
+	public AssemblyVerificationException(string message, Exception inner)
+		: base(message, inner)
+	{
+	}
+}
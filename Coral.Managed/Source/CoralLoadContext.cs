@@ -0,0 +1,288 @@
+using Coral.Managed.Interop;
+
+using System;
+using System.Collections.Generic;
+using System.IO;
+using System.Reflection;
+using System.Reflection.Metadata;
+using System.Reflection.PortableExecutable;
+using System.Runtime.InteropServices;
+using System.Runtime.Loader;
+using System.Security.Cryptography;
+
+namespace Coral.Managed;
+
+using static ManagedHost;
+
+[StructLayout(LayoutKind.Sequential)]
+public struct AssemblyResolution
+{
+	public NativeString Path;
+	public IntPtr AssemblyData;
+	public long AssemblyDataLength;
+	public IntPtr SymbolsData;
+	public long SymbolsDataLength;
+}
+
+internal sealed class CoralLoadContext : AssemblyLoadContext
+{
+	private readonly List<string> m_ProbingDirectories = new();
+	private AssemblyDependencyResolver? m_DependencyResolver;
+
+	private unsafe delegate*<IntPtr, NativeString, NativeString, AssemblyResolution*, Bool32> m_AssemblyResolver;
+	private unsafe delegate*<IntPtr, NativeString, NativeString*, Bool32> m_UnmanagedResolver;
+	private IntPtr m_ResolverUserData;
+
+	private HashSet<string>? m_AllowedHashes;
+	private HashSet<string>? m_RequiredPublicKeyTokens;
+	private unsafe delegate*<IntPtr, byte*, long, NativeString*, Bool32> m_Verifier;
+	private IntPtr m_VerifierUserData;
+	private SandboxPolicy? m_SandboxPolicy;
+
+	public CoralLoadContext(string InName, bool InIsCollectible = true)
+		: base(InName, InIsCollectible)
+	{
+	}
+
+	internal void AddProbingDirectory(string InDirectory)
+	{
+		if (!m_ProbingDirectories.Contains(InDirectory))
+			m_ProbingDirectories.Add(InDirectory);
+	}
+
+	internal void SetDependencyResolver(string InComponentAssemblyPath)
+	{
+		m_DependencyResolver = new AssemblyDependencyResolver(InComponentAssemblyPath);
+	}
+
+	internal unsafe void SetResolvers(delegate*<IntPtr, NativeString, NativeString, AssemblyResolution*, Bool32> InAssemblyResolver, delegate*<IntPtr, NativeString, NativeString*, Bool32> InUnmanagedResolver, IntPtr InUserData)
+	{
+		m_AssemblyResolver = InAssemblyResolver;
+		m_UnmanagedResolver = InUnmanagedResolver;
+		m_ResolverUserData = InUserData;
+	}
+
+	internal unsafe void ClearResolvers()
+	{
+		m_AssemblyResolver = null;
+		m_UnmanagedResolver = null;
+		m_ResolverUserData = IntPtr.Zero;
+	}
+
+	internal unsafe void SetVerificationPolicy(HashSet<string>? InAllowedHashes, HashSet<string>? InRequiredPublicKeyTokens, delegate*<IntPtr, byte*, long, NativeString*, Bool32> InVerifier, IntPtr InUserData)
+	{
+		m_AllowedHashes = InAllowedHashes;
+		m_RequiredPublicKeyTokens = InRequiredPublicKeyTokens;
+		m_Verifier = InVerifier;
+		m_VerifierUserData = InUserData;
+	}
+
+	internal void SetSandboxPolicy(SandboxPolicy? InSandboxPolicy)
+	{
+		m_SandboxPolicy = InSandboxPolicy;
+	}
+
+	internal unsafe bool HasVerificationPolicy => m_AllowedHashes != null || m_RequiredPublicKeyTokens != null || m_Verifier != null || m_SandboxPolicy != null;
+
+	/// <summary>
+	/// Throws an AssemblyVerificationException if the assembly doesn't pass the verification policy,
+	/// or a SandboxViolationException if it uses APIs the sandbox policy forbids
+	/// </summary>
+	internal unsafe void Verify(ReadOnlySpan<byte> InAssemblyData)
+	{
+		if (m_AllowedHashes != null)
+		{
+			var hash = Convert.ToHexString(SHA256.HashData(InAssemblyData)).ToLowerInvariant();
+
+			if (!m_AllowedHashes.Contains(hash))
+				throw new AssemblyVerificationException($"SHA-256 '{hash}' is not one of the allowed hashes");
+		}
+
+		fixed (byte* assemblyData = InAssemblyData)
+		{
+			if (m_RequiredPublicKeyTokens != null)
+			{
+				// NOTE: .NET doesn't validate strong-name signatures, this only checks which key the assembly claims to be signed with
+				using var peReader = new PEReader(assemblyData, InAssemblyData.Length);
+				var publicKeyToken = peReader.GetMetadataReader().GetAssemblyDefinition().GetAssemblyName().GetPublicKeyToken();
+
+				if (publicKeyToken == null || publicKeyToken.Length == 0)
+					throw new AssemblyVerificationException("Assembly isn't strong-name signed");
+
+				var token = Convert.ToHexString(publicKeyToken).ToLowerInvariant();
+
+				if (!m_RequiredPublicKeyTokens.Contains(token))
+					throw new AssemblyVerificationException($"Public key token '{token}' is not one of the required tokens");
+			}
+
+			m_SandboxPolicy?.Check(assemblyData, InAssemblyData.Length);
+
+			if (m_Verifier != null)
+			{
+				NativeString reason = NativeString.Null();
+
+				if (!m_Verifier(m_VerifierUserData, assemblyData, InAssemblyData.Length, &reason))
+					throw new AssemblyVerificationException((string?)reason ?? "Rejected by the host");
+			}
+		}
+	}
+
+	/// <summary>
+	/// Same as LoadFromStream, but runs the verification policy first (if there is one).
+	/// </summary>
+	internal Assembly LoadFromStreamVerified(Stream InAssemblyStream, Stream? InSymbolsStream = null)
+	{
+		if (HasVerificationPolicy)
+		{
+			// NOTE: Verify the exact bytes that get loaded, reading the source a second time would allow it to change in between
+			using var assemblyCopy = new MemoryStream();
+			InAssemblyStream.CopyTo(assemblyCopy);
+
+			Verify(assemblyCopy.GetBuffer().AsSpan(0, (int)assemblyCopy.Length));
+
+			assemblyCopy.Position = 0;
+			return InSymbolsStream != null ? LoadFromStream(assemblyCopy, InSymbolsStream) : LoadFromStream(assemblyCopy);
+		}
+
+		return InSymbolsStream != null ? LoadFromStream(InAssemblyStream, InSymbolsStream) : LoadFromStream(InAssemblyStream);
+	}
+
+	private Assembly LoadFromAssemblyPathVerified(string InAssemblyPath)
+	{
+		if (!HasVerificationPolicy)
+			return LoadFromAssemblyPath(InAssemblyPath);
+
+		using var stream = File.OpenRead(InAssemblyPath);
+		return LoadFromStreamVerified(stream);
+	}
+
+	protected override Assembly? Load(AssemblyName InAssemblyName)
+	{
+		// NOTE: Shared assemblies win over private copies, otherwise their types would have a different identity in every context
+		var sharedAssembly = AssemblyLoader.FindSharedAssembly(InAssemblyName);
+
+		if (sharedAssembly != null)
+			return sharedAssembly;
+
+		// NOTE: Only the .deps.json is consulted here, it never points at framework assemblies.
+		//		 Probing directories and the host resolver run from ResolveExternalAssembly, after the default context had a go.
+		var assemblyPath = m_DependencyResolver?.ResolveAssemblyToPath(InAssemblyName);
+
+		if (assemblyPath == null)
+			return null;
+
+		try
+		{
+			return LoadFromAssemblyPathVerified(assemblyPath);
+		}
+		catch (Exception ex) when (ex is AssemblyVerificationException or SandboxViolationException)
+		{
+			LogMessage($"Refusing to load dependency '{assemblyPath}': {ex.Message}", MessageLevel.Error);
+			return null;
+		}
+	}
+
+	internal unsafe Assembly? ResolveExternalAssembly(AssemblyName InAssemblyName)
+	{
+		try
+		{
+			foreach (var directory in m_ProbingDirectories)
+			{
+				var assemblyPath = Path.Combine(directory, $"{InAssemblyName.Name}.dll");
+
+				if (File.Exists(assemblyPath))
+					return LoadFromAssemblyPathVerified(assemblyPath);
+			}
+
+			if (m_AssemblyResolver == null)
+				return null;
+
+			using NativeString name = InAssemblyName.Name;
+			using NativeString version = InAssemblyName.Version?.ToString();
+			AssemblyResolution resolution = default;
+
+			if (!m_AssemblyResolver(m_ResolverUserData, name, version, &resolution))
+				return null;
+
+			string? resolvedPath = resolution.Path;
+
+			if (resolvedPath != null)
+				return LoadFromAssemblyPathVerified(resolvedPath);
+
+			if (resolution.AssemblyData == IntPtr.Zero)
+				return null;
+
+			using var stream = new UnmanagedMemoryStream((byte*)resolution.AssemblyData, resolution.AssemblyDataLength);
+
+			if (resolution.SymbolsData == IntPtr.Zero)
+				return LoadFromStreamVerified(stream);
+
+			using var symbolsStream = new UnmanagedMemoryStream((byte*)resolution.SymbolsData, resolution.SymbolsDataLength);
+			return LoadFromStreamVerified(stream, symbolsStream);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+			return null;
+		}
+	}
+
+	protected override unsafe IntPtr LoadUnmanagedDll(string InUnmanagedDllName)
+	{
+		try
+		{
+			var libraryPath = m_DependencyResolver?.ResolveUnmanagedDllToPath(InUnmanagedDllName);
+
+			if (libraryPath != null)
+				return LoadUnmanagedDllFromPath(libraryPath);
+
+			foreach (var directory in m_ProbingDirectories)
+			{
+				foreach (var candidate in GetUnmanagedLibraryCandidates(InUnmanagedDllName))
+				{
+					var candidatePath = Path.Combine(directory, candidate);
+
+					if (File.Exists(candidatePath))
+						return LoadUnmanagedDllFromPath(candidatePath);
+				}
+			}
+
+			if (m_UnmanagedResolver == null)
+				return IntPtr.Zero;
+
+			using NativeString name = InUnmanagedDllName;
+			NativeString resolvedPath = NativeString.Null();
+
+			if (!m_UnmanagedResolver(m_ResolverUserData, name, &resolvedPath))
+				return IntPtr.Zero;
+
+			string? path = resolvedPath;
+			return path != null ? LoadUnmanagedDllFromPath(path) : IntPtr.Zero;
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+			return IntPtr.Zero;
+		}
+	}
+
+	private static IEnumerable<string> GetUnmanagedLibraryCandidates(string InName)
+	{
+		yield return InName;
+
+		if (OperatingSystem.IsWindows())
+		{
+			yield return $"{InName}.dll";
+		}
+		else if (OperatingSystem.IsMacOS())
+		{
+			yield return $"{InName}.dylib";
+			yield return $"lib{InName}.dylib";
+		}
+		else
+		{
+			yield return $"{InName}.so";
+			yield return $"lib{InName}.so";
+		}
+	}
+}
@@ -0,0 +1,42 @@
+﻿using Coral.Managed.Interop;
+
+using System;
+using System.ComponentModel;
+
+namespace Coral.Managed;
+
+/// <summary>
+/// Thrown by an internal call whose native function returned an error, and fails the task of an async internal call
+/// whose native future returned an error or that never completed
+/// </summary>
+public class InternalCallFailedException : Exception
+{
+	public InternalCallFailedException()
+	{
+	}
+
+	public InternalCallFailedException(string message)
+		: base(message)
+	{
+	}
+
+	public InternalCallFailedException(string message, Exception inner)
+		: base(message, inner)
+	{
+	}
+
+	/// <summary>
+	/// Called by the stubs of internal calls that report errors, throws (and frees) the error InError was set to
+	/// </summary>
+	[EditorBrowsable(EditorBrowsableState.Never)]
+	public static void ThrowIfFailed(ref NativeString InError, string InFieldName)
+	{
+		if (InError.m_NativeString == IntPtr.Zero)
+			return;
+
+		string? message = InError;
+		InError.Dispose();
+
+		throw new InternalCallFailedException($"Internal call '{InFieldName}' failed: {message}");
+	}
+}
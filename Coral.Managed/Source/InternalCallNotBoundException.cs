@@ -0,0 +1,26 @@
+﻿using System;
+
+namespace Coral.Managed;
+
+/// <summary>
+/// Thrown by the stub Sharpen puts in an internal call field that was never bound to a native function
+/// </summary>
+public class InternalCallNotBoundException : Exception
+{
+	public string? FieldName { get; }
+
+	public InternalCallNotBoundException()
+	{
+	}
+
+	public InternalCallNotBoundException(string fieldName)
+		: base($"Internal call '{fieldName}' was never bound to a native function.")
+	{
+		FieldName = fieldName;
+	}
+
+	public InternalCallNotBoundException(string message, Exception inner)
+		: base(message, inner)
+	{
+	}
+}
@@ -0,0 +1,205 @@
+using System;
+using System.Collections.Concurrent;
+using System.Collections.Generic;
+using System.ComponentModel;
+using System.Reflection;
+using System.Runtime.InteropServices;
+using System.Threading;
+
+namespace Coral.Managed.Interop;
+
+using static ManagedHost;
+
+/// <summary>
+/// While the internal calls of an assembly are paused (e.g while the native library that implements them is reloaded),
+/// their fields point at stubs that wait until they're resumed, and then call whatever the field was rebound to.
+/// Pauses nest, the calls resume once every pause is resumed.
+/// </summary>
+public static class InternalCallPauses
+{
+	private sealed class WaitingStub
+	{
+		public IntPtr Stub;
+		public IntPtr Target;
+		// NOTE: Replaces the context waiting closure calls were made with, which can belong to the function they were rebound from
+		public IntPtr Context;
+	}
+
+	private sealed class PauseState
+	{
+		public readonly ManualResetEventSlim Resumed = new(true);
+		public readonly Dictionary<FieldInfo, int> StubIds = new();
+		public readonly List<WaitingStub> WaitingStubs = new();
+		public int PauseCount;
+		public bool IsForgotten;
+	}
+
+	private static readonly ConcurrentDictionary<int, PauseState> s_States = new();
+
+	/// <summary>
+	/// Called by the waiting stubs, blocks while the internal calls of the assembly are paused.
+	/// Returns the function the stub InStubId calls now, and the context it passes if it's a closure.
+	/// </summary>
+	[EditorBrowsable(EditorBrowsableState.Never)]
+	public static IntPtr WaitForResume(int InAssemblyId, int InStubId, out IntPtr OutContext)
+	{
+		if (!s_States.TryGetValue(InAssemblyId, out var state))
+			throw new InvalidOperationException($"The internal calls of assembly '{InAssemblyId}' were unloaded while a call waited for them.");
+
+		while (true)
+		{
+			state.Resumed.Wait();
+
+			lock (state)
+			{
+				if (state.IsForgotten)
+					throw new InvalidOperationException($"The internal calls of assembly '{InAssemblyId}' were unloaded while a call waited for them.");
+
+				// NOTE: Paused again before this call got the lock
+				if (state.PauseCount > 0)
+					continue;
+
+				var stub = state.WaitingStubs[InStubId];
+				OutContext = stub.Context;
+				return stub.Target;
+			}
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void PauseInternalCalls(int InAssemblyId)
+	{
+		try
+		{
+			if (!AssemblyLoader.TryGetAssembly(InAssemblyId, out var assembly) || assembly == null)
+			{
+				LogMessage($"Couldn't pause internal calls of assembly '{InAssemblyId}', assembly not found.", MessageLevel.Error);
+				return;
+			}
+
+			var state = s_States.GetOrAdd(InAssemblyId, _ => new());
+
+			lock (state)
+			{
+				if (state.PauseCount++ > 0)
+					return;
+
+				state.Resumed.Reset();
+
+				foreach (var field in InternalCallsManager.GetFunctionPointerFields(assembly))
+				{
+					var function = InternalCallsManager.GetFunctionPointer(field);
+
+					if (function == IntPtr.Zero)
+						continue;
+
+					try
+					{
+						var contextField = GetContextField(field);
+
+						if (!state.StubIds.TryGetValue(field, out var stubId))
+						{
+							stubId = state.WaitingStubs.Count;
+							var passesContext = contextField != null && field.FieldType.GetFunctionPointerParameterTypes() is [var first, ..] && first == typeof(IntPtr);
+							state.WaitingStubs.Add(new WaitingStub { Stub = InternalCallStubs.CreateWaiting(assembly, field.FieldType, InAssemblyId, stubId, passesContext) });
+							state.StubIds.Add(field, stubId);
+						}
+
+						var stub = state.WaitingStubs[stubId];
+						stub.Target = function;
+						stub.Context = GetContext(contextField);
+						field.SetValue(null, stub.Stub);
+					}
+					catch (Exception ex)
+					{
+						LogMessage($"Couldn't pause internal call '{field.DeclaringType?.FullName}.{field.Name}', it keeps running: {ex.Message}", MessageLevel.Error);
+					}
+				}
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void ResumeInternalCalls(int InAssemblyId)
+	{
+		try
+		{
+			if (!s_States.TryGetValue(InAssemblyId, out var state))
+				return;
+
+			lock (state)
+			{
+				if (state.PauseCount == 0 || --state.PauseCount > 0)
+					return;
+
+				foreach (var (field, stubId) in state.StubIds)
+				{
+					var stub = state.WaitingStubs[stubId];
+
+					if (InternalCallsManager.GetFunctionPointer(field) == stub.Stub)
+						field.SetValue(null, stub.Target);
+				}
+
+				state.Resumed.Set();
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	/// <summary>
+	/// Points the waiting stub in InField at InFunction instead of the old function, false if InField isn't paused.
+	/// Waiting closure calls pass the context the field has now, so set that first.
+	/// </summary>
+	internal static bool TryRetarget(FieldInfo InField, IntPtr InFunction)
+	{
+		if (InField.DeclaringType == null || !s_States.TryGetValue(AssemblyLoader.GetAssemblyId(InField.DeclaringType.Assembly), out var state))
+			return false;
+
+		lock (state)
+		{
+			if (state.PauseCount == 0 || !state.StubIds.TryGetValue(InField, out var stubId))
+				return false;
+
+			var stub = state.WaitingStubs[stubId];
+
+			if (InternalCallsManager.GetFunctionPointer(InField) != stub.Stub)
+				return false;
+
+			stub.Target = InFunction;
+			stub.Context = GetContext(GetContextField(InField));
+			return true;
+		}
+	}
+
+	internal static void Forget(int InAssemblyId)
+	{
+		if (!s_States.TryRemove(InAssemblyId, out var state))
+			return;
+
+		lock (state)
+		{
+			state.IsForgotten = true;
+			state.Resumed.Set();
+		}
+	}
+
+	private static FieldInfo? GetContextField(FieldInfo InField)
+	{
+		var bindingFlags = BindingFlags.Static | BindingFlags.Public | BindingFlags.NonPublic | BindingFlags.DeclaredOnly;
+		var contextField = InField.DeclaringType?.GetField(InField.Name + "Context", bindingFlags);
+
+		return contextField?.FieldType == typeof(IntPtr) ? contextField : null;
+	}
+
+	private static IntPtr GetContext(FieldInfo? InContextField)
+	{
+		return InContextField != null ? (IntPtr)InContextField.GetValue(null)! : IntPtr.Zero;
+	}
+}
@@ -0,0 +1,177 @@
+using System;
+using System.Collections.Generic;
+using System.Linq;
+using System.Reflection;
+using System.Reflection.Emit;
+using System.Runtime.CompilerServices;
+using System.Runtime.InteropServices;
+
+namespace Coral.Managed.Interop;
+
+/// <summary>
+/// Emits functions with the signature of an internal call field, that either throw InternalCallNotBoundException
+/// (so calling a field that was never bound doesn't jump to null), wait for paused internal calls to resume,
+/// or throw the errors native functions report as InternalCallFailedException
+/// </summary>
+internal static class InternalCallStubs
+{
+	private sealed class StubModule
+	{
+		public ModuleBuilder Module = null!;
+		public int NextStubId;
+		// NOTE: The pointers are only valid while the stub methods and delegates they point to are alive
+		public readonly List<object> Stubs = new();
+	}
+
+	// NOTE: One collectible module per assembly, so the stubs (which can reference its types) don't keep its context alive
+	private static readonly ConditionalWeakTable<Assembly, StubModule> s_Modules = new();
+
+	// NOTE: Delegates of a type from a collectible assembly can't be marshalled, so the stubs of unmanaged fields go into
+	//		 a module that's never collected instead. Their signatures can't reference types of collectible assemblies.
+	private static readonly StubModule s_UnmanagedModule = new()
+	{
+		Module = AssemblyBuilder
+			.DefineDynamicAssembly(new AssemblyName("Coral.Managed.InternalCallStubs"), AssemblyBuilderAccess.Run)
+			.DefineDynamicModule("InternalCallStubs")
+	};
+
+	internal static IntPtr CreateThrowing(Assembly InAssembly, Type InFunctionPointerType, string InFieldName)
+	{
+		return Create(InAssembly, InFunctionPointerType, (il, _) =>
+		{
+			il.Emit(OpCodes.Ldstr, InFieldName);
+			il.Emit(OpCodes.Newobj, typeof(InternalCallNotBoundException).GetConstructor(new[] { typeof(string) })!);
+			il.Emit(OpCodes.Throw);
+		});
+	}
+
+	/// <summary>
+	/// Waits until the internal calls of the assembly are resumed and then calls the function InternalCallPauses.WaitForResume
+	/// returns for InStubId. If InPassesContext is set the first argument is replaced by the context it returns.
+	/// </summary>
+	internal static IntPtr CreateWaiting(Assembly InAssembly, Type InFunctionPointerType, int InAssemblyId, int InStubId, bool InPassesContext)
+	{
+		return Create(InAssembly, InFunctionPointerType, (il, parameterTypes) =>
+		{
+			var returnType = InFunctionPointerType.GetFunctionPointerReturnType();
+			var context = il.DeclareLocal(typeof(IntPtr));
+			var target = il.DeclareLocal(typeof(IntPtr));
+
+			il.Emit(OpCodes.Ldc_I4, InAssemblyId);
+			il.Emit(OpCodes.Ldc_I4, InStubId);
+			il.Emit(OpCodes.Ldloca, context);
+			il.Emit(OpCodes.Call, typeof(InternalCallPauses).GetMethod(nameof(InternalCallPauses.WaitForResume))!);
+			il.Emit(OpCodes.Stloc, target);
+
+			for (int i = 0; i < parameterTypes.Length; i++)
+			{
+				if (i == 0 && InPassesContext)
+					il.Emit(OpCodes.Ldloc, context);
+				else
+					il.Emit(OpCodes.Ldarg, (short)i);
+			}
+
+			il.Emit(OpCodes.Ldloc, target);
+
+			if (InFunctionPointerType.IsUnmanagedFunctionPointer)
+				il.EmitCalli(OpCodes.Calli, CallingConvention.Winapi, returnType, parameterTypes);
+			else
+				il.EmitCalli(OpCodes.Calli, CallingConventions.Standard, returnType, parameterTypes, null);
+
+			il.Emit(OpCodes.Ret);
+		});
+	}
+
+	/// <summary>
+	/// Calls InFunction with the field's arguments and a pointer to an error string it sets if it fails,
+	/// which is thrown as an InternalCallFailedException
+	/// </summary>
+	internal static IntPtr CreateReporting(Assembly InAssembly, Type InFunctionPointerType, IntPtr InFunction, string InFieldName)
+	{
+		return Create(InAssembly, InFunctionPointerType, (il, parameterTypes) =>
+		{
+			var returnType = InFunctionPointerType.GetFunctionPointerReturnType();
+			var error = il.DeclareLocal(typeof(NativeString));
+			var result = returnType != typeof(void) ? il.DeclareLocal(returnType) : null;
+
+			for (int i = 0; i < parameterTypes.Length; i++)
+				il.Emit(OpCodes.Ldarg, (short)i);
+
+			il.Emit(OpCodes.Ldloca, error);
+			il.Emit(OpCodes.Conv_U);
+			il.Emit(OpCodes.Ldc_I8, (long)InFunction);
+			il.Emit(OpCodes.Conv_I);
+			il.EmitCalli(OpCodes.Calli, CallingConvention.Winapi, returnType, parameterTypes.Append(typeof(IntPtr)).ToArray());
+
+			if (result != null)
+				il.Emit(OpCodes.Stloc, result);
+
+			il.Emit(OpCodes.Ldloca, error);
+			il.Emit(OpCodes.Ldstr, InFieldName);
+			il.Emit(OpCodes.Call, typeof(InternalCallFailedException).GetMethod(nameof(InternalCallFailedException.ThrowIfFailed))!);
+
+			if (result != null)
+				il.Emit(OpCodes.Ldloc, result);
+
+			il.Emit(OpCodes.Ret);
+		});
+	}
+
+	private static IntPtr Create(Assembly InAssembly, Type InFunctionPointerType, Action<ILGenerator, Type[]> InEmitBody)
+	{
+		var parameterTypes = InFunctionPointerType.GetFunctionPointerParameterTypes();
+		var returnType = InFunctionPointerType.GetFunctionPointerReturnType();
+
+		var stubs = InFunctionPointerType.IsUnmanagedFunctionPointer ? s_UnmanagedModule : s_Modules.GetValue(InAssembly, assembly => new StubModule
+		{
+			Module = AssemblyBuilder
+				.DefineDynamicAssembly(new AssemblyName($"{assembly.GetName().Name}.InternalCallStubs"), AssemblyBuilderAccess.RunAndCollect)
+				.DefineDynamicModule("InternalCallStubs")
+		});
+
+		if (stubs == s_UnmanagedModule && parameterTypes.Append(returnType).Any(type => type.Assembly.IsCollectible))
+			throw new NotSupportedException($"Can't stub '{InFunctionPointerType}', its signature uses types of a collectible assembly.");
+
+		lock (stubs)
+		{
+			int stubId = stubs.NextStubId++;
+
+			var stubBuilder = stubs.Module.DefineType($"Stub{stubId}", TypeAttributes.Public | TypeAttributes.Abstract | TypeAttributes.Sealed);
+			var methodBuilder = stubBuilder.DefineMethod("Invoke", MethodAttributes.Public | MethodAttributes.Static, returnType, parameterTypes);
+			InEmitBody(methodBuilder.GetILGenerator(), parameterTypes);
+
+			var stub = stubBuilder.CreateType()!.GetMethod("Invoke")!;
+
+			if (!InFunctionPointerType.IsUnmanagedFunctionPointer)
+			{
+				stubs.Stubs.Add(stub);
+				return stub.MethodHandle.GetFunctionPointer();
+			}
+
+			// NOTE: UnmanagedCallersOnly methods can't throw, so unmanaged fields get a marshalled delegate instead
+			var stubDelegate = stub.CreateDelegate(DefineDelegateType(stubs.Module, $"StubDelegate{stubId}", returnType, parameterTypes));
+			stubs.Stubs.Add(stubDelegate);
+			return Marshal.GetFunctionPointerForDelegate(stubDelegate);
+		}
+	}
+
+	private static Type DefineDelegateType(ModuleBuilder InModule, string InName, Type InReturnType, Type[] InParameterTypes)
+	{
+		var delegateType = InModule.DefineType(InName, TypeAttributes.Public | TypeAttributes.Sealed | TypeAttributes.AutoClass, typeof(MulticastDelegate));
+
+		var constructor = delegateType.DefineConstructor(
+			MethodAttributes.Public | MethodAttributes.HideBySig | MethodAttributes.SpecialName | MethodAttributes.RTSpecialName,
+			CallingConventions.Standard,
+			new[] { typeof(object), typeof(IntPtr) });
+		constructor.SetImplementationFlags(MethodImplAttributes.Runtime | MethodImplAttributes.Managed);
+
+		var invoke = delegateType.DefineMethod(
+			"Invoke",
+			MethodAttributes.Public | MethodAttributes.HideBySig | MethodAttributes.NewSlot | MethodAttributes.Virtual,
+			InReturnType,
+			InParameterTypes);
+		invoke.SetImplementationFlags(MethodImplAttributes.Runtime | MethodImplAttributes.Managed);
+
+		return delegateType.CreateType()!;
+	}
+}
@@ -0,0 +1,156 @@
+using System;
+using System.Runtime.CompilerServices;
+using System.Runtime.InteropServices;
+using System.Threading.Tasks;
+
+namespace Coral.Managed.Interop;
+
+using static ManagedHost;
+
+/// <summary>
+/// Tasks that async internal calls complete from native code once their future resolves.
+/// The handle is passed as the last argument of the call, which takes ownership of it.
+/// </summary>
+public static class InternalCallTasks
+{
+	private abstract class PendingTask
+	{
+		public abstract void Complete(IntPtr InValue, ManagedType InValueType);
+		public abstract void Fail(Exception InException);
+	}
+
+	private sealed class PendingTask<T> : PendingTask
+	{
+		// NOTE: Otherwise the continuations would run on the native thread that completes the task
+		public readonly TaskCompletionSource<T> Source = new(TaskCreationOptions.RunContinuationsAsynchronously);
+		private readonly ManagedType m_ValueType;
+
+		public PendingTask(ManagedType InValueType)
+		{
+			m_ValueType = InValueType;
+		}
+
+		public override void Complete(IntPtr InValue, ManagedType InValueType)
+		{
+			if (InValueType != m_ValueType)
+			{
+				Fail(new InternalCallFailedException($"The async internal call completed with a {InValueType} value, but its task expects '{typeof(T)}'."));
+				return;
+			}
+
+			Source.SetResult(ReadValue(InValue));
+		}
+
+		public override void Fail(Exception InException)
+		{
+			Source.SetException(InException);
+		}
+
+		private unsafe T ReadValue(IntPtr InValue)
+		{
+			switch (m_ValueType)
+			{
+				case ManagedType.Void:
+					return default!;
+				case ManagedType.String:
+					string? value = Marshal.PtrToStructure<NativeString>(InValue);
+					return (T)(object)value!;
+				case ManagedType.Bool:
+					return (T)(object)(Marshal.ReadByte(InValue) != 0);
+				case ManagedType.Pointer when !typeof(T).IsValueType:
+					// NOTE: Objects are returned as a GCHandle the task takes ownership of
+					var handle = GCHandle.FromIntPtr(Marshal.ReadIntPtr(InValue));
+					var target = handle.Target;
+					handle.Free();
+					return (T)target!;
+				default:
+					return Unsafe.ReadUnaligned<T>(InValue.ToPointer());
+			}
+		}
+	}
+
+	/// <summary>
+	/// Creates the task an async internal call completes with a value of type T, e.g
+	/// <code>
+	/// var task = InternalCallTasks.Create&lt;string&gt;(out var handle);
+	/// LoadText(LoadTextContext, path, handle);
+	/// return task;
+	/// </code>
+	/// </summary>
+	public static Task<T> Create<T>(out IntPtr OutTask)
+	{
+		var valueType = GetValueType(typeof(T));
+
+		if (valueType == ManagedType.Unknown)
+			throw new NotSupportedException($"Async internal calls can't complete a task with a '{typeof(T)}'.");
+
+		var task = new PendingTask<T>(valueType);
+		OutTask = GCHandle.ToIntPtr(GCHandle.Alloc(task));
+		return task.Source.Task;
+	}
+
+	/// <summary>
+	/// Creates the task an async internal call completes without a value
+	/// </summary>
+	public static Task Create(out IntPtr OutTask)
+	{
+		var task = new PendingTask<object?>(ManagedType.Void);
+		OutTask = GCHandle.ToIntPtr(GCHandle.Alloc(task));
+		return task.Source.Task;
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void CompleteInternalCallTask(IntPtr InTask, IntPtr InValue, ManagedType InValueType)
+	{
+		try
+		{
+			var task = TakeTask(InTask);
+
+			try
+			{
+				task.Complete(InValue, InValueType);
+			}
+			catch (Exception ex)
+			{
+				task.Fail(ex);
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	[UnmanagedCallersOnly]
+	internal static void FailInternalCallTask(IntPtr InTask, NativeString InMessage)
+	{
+		try
+		{
+			string? message = InMessage;
+			TakeTask(InTask).Fail(new InternalCallFailedException(message ?? "The async internal call failed."));
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	private static PendingTask TakeTask(IntPtr InTask)
+	{
+		var handle = GCHandle.FromIntPtr(InTask);
+		var task = (PendingTask)handle.Target!;
+		handle.Free();
+		return task;
+	}
+
+	private static ManagedType GetValueType(Type InType)
+	{
+		if (InType == typeof(NativeString))
+			return ManagedType.Unknown;
+
+		if (InType != typeof(string) && !InType.IsValueType)
+			return ManagedType.Pointer;
+
+		return TypeInterface.GetManagedType(InType);
+	}
+}
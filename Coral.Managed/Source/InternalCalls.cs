@@ -1,4 +1,5 @@
 using System;
+using System.Collections.Generic;
 using System.Linq;
 using System.Runtime.InteropServices;
 using System.Reflection;
@@ -12,16 +13,71 @@ public readonly struct InternalCall
 {
 	private readonly IntPtr m_NamePtr;
 	public readonly IntPtr NativeFunctionPtr;
+	private readonly IntPtr m_ParameterTypes;
+	/// <summary>
+	/// -1 if the signature of the native function is unknown, in which case it isn't validated
+	/// </summary>
+	public readonly int ParameterCount;
+	private readonly ManagedType m_ReturnType;
+	/// <summary>
+	/// Set for closures, the native function takes it as its first argument
+	/// </summary>
+	public readonly IntPtr Context;
+	/// <summary>
+	/// The native function takes a NativeString* after the field's parameters, which it sets to the error if it fails
+	/// </summary>
+	public readonly Bool32 ReportsErrors;
+	// NOTE: Keeps the size a multiple of 8, like the native struct
+	private readonly int m_Padding;
 
 	public string? Name => Marshal.PtrToStringAuto(m_NamePtr);
+
+	internal ManagedType ReturnType => m_ReturnType;
+
+	internal unsafe ManagedType GetParameterType(int InIndex) => ((ManagedType*)m_ParameterTypes)[InIndex];
+}
+
+internal enum InternalCallErrorReason
+{
+	InvalidName,
+	TypeNotFound,
+	FieldNotFound,
+	NotAFunctionPointer,
+	SignatureMismatch,
+	ContextFieldNotFound,
+	Exception
+}
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct NativeInternalCallError
+{
+	public NativeString ClassName;
+	public NativeString FieldName;
+	public InternalCallErrorReason Reason;
+	public NativeString Message;
+}
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct NativeUnboundInternalCall
+{
+	public NativeString ClassName;
+	public NativeString FieldName;
 }
 
 internal static class InternalCallsManager
 {
+	[ThreadStatic] private static List<(string ClassName, string FieldName, InternalCallErrorReason Reason, string Message)>? s_LastErrors;
+	[ThreadStatic] private static List<(string ClassName, string FieldName)>? s_LastUnboundCalls;
+
+	/// <summary>
+	/// Binds every internal call it can and returns the number that failed, see GetLastInternalCallErrors
+	/// </summary>
 	[UnmanagedCallersOnly]
-	internal static void SetInternalCalls(IntPtr InInternalCalls, int InLength)
+	internal static int SetInternalCalls(IntPtr InInternalCalls, int InLength)
 	{
 		var internalCalls = new NativeArray<InternalCall>(InInternalCalls, IntPtr.Zero, InLength);
+		var errors = s_LastErrors ??= new();
+		errors.Clear();
 
 		try
 		{
@@ -30,41 +86,199 @@ internal static class InternalCallsManager
 				var internalCall = internalCalls[i];
 				var name = internalCall.Name;
 
-				if (name == null)
+				var fieldNameStart = name?.IndexOf('+') ?? -1;
+				var fieldNameEnd = fieldNameStart < 0 ? -1 : name!.IndexOf(",", fieldNameStart, StringComparison.CurrentCulture);
+
+				if (name == null || fieldNameEnd < 0)
 				{
-					LogMessage($"Cannot register internal at index '{i}' call with null name!", MessageLevel.Error);
+					var message = $"Cannot register internal call at index '{i}' with invalid name '{name}'!";
+					LogMessage(message, MessageLevel.Error);
+					errors.Add(("", "", InternalCallErrorReason.InvalidName, message));
 					continue;
 				}
 
-				var fieldNameStart = name.IndexOf('+');
-				var fieldNameEnd = name.IndexOf(",", fieldNameStart, StringComparison.CurrentCulture);
 				var fieldName = name.Substring(fieldNameStart + 1, fieldNameEnd - fieldNameStart - 1);
+				var className = name.Substring(0, fieldNameStart);
 				var containingTypeName = name.Remove(fieldNameStart, fieldNameEnd - fieldNameStart);
 
-				var type = TypeInterface.FindType(containingTypeName);
-
-				if (type == null)
+				void Fail(InternalCallErrorReason InReason, string InMessage)
 				{
-					LogMessage($"Cannot register internal call '{name}', failed to type '{containingTypeName}'.", MessageLevel.Error);
-					continue;
+					LogMessage(InMessage, MessageLevel.Error);
+					errors.Add((className, fieldName, InReason, InMessage));
 				}
 
-				var bindingFlags = BindingFlags.Static | BindingFlags.NonPublic;
-				var field = type.GetFields(bindingFlags).FirstOrDefault(field => field.Name == fieldName);
+				// NOTE: One call throwing doesn't stop the rest from being bound, it's reported as its own error
+				try
+				{
+					var type = TypeInterface.FindType(containingTypeName);
+
+					if (type == null)
+					{
+						Fail(InternalCallErrorReason.TypeNotFound, $"Cannot register internal call '{name}', failed to find type '{containingTypeName}'.");
+						continue;
+					}
 
-				if (field == null)
+					var bindingFlags = BindingFlags.Static | BindingFlags.NonPublic;
+					var field = type.GetFields(bindingFlags).FirstOrDefault(field => field.Name == fieldName);
+
+					if (field == null)
+					{
+						Fail(InternalCallErrorReason.FieldNotFound, $"Cannot register internal '{name}', failed to find it in type '{containingTypeName}'");
+						continue;
+					}
+
+					// NOTE: Managed function pointers use the managed calling convention, which native functions can't be called with
+					if (!field.FieldType.IsUnmanagedFunctionPointer)
+					{
+						Fail(InternalCallErrorReason.NotAFunctionPointer, $"Field '{name}' is not an unmanaged function pointer type (delegate* unmanaged<...>)!");
+						continue;
+					}
+
+					var mismatch = ValidateSignature(field.FieldType, internalCall);
+
+					if (mismatch != null)
+					{
+						Fail(InternalCallErrorReason.SignatureMismatch, $"Cannot register internal call '{name}', {mismatch}");
+						continue;
+					}
+
+					if (internalCall.Context != IntPtr.Zero)
+					{
+						var contextFieldName = fieldName + "Context";
+						var contextField = type.GetFields(bindingFlags).FirstOrDefault(field => field.Name == contextFieldName);
+
+						if (contextField == null || contextField.FieldType != typeof(IntPtr))
+						{
+							Fail(InternalCallErrorReason.ContextFieldNotFound, $"Cannot register internal call '{name}', closures need a static IntPtr field '{contextFieldName}' to pass their context back.");
+							continue;
+						}
+
+						contextField.SetValue(null, internalCall.Context);
+					}
+
+					var function = internalCall.ReportsErrors
+						? InternalCallStubs.CreateReporting(type.Assembly, field.FieldType, internalCall.NativeFunctionPtr, $"{className}.{fieldName}")
+						: internalCall.NativeFunctionPtr;
+
+					// NOTE: If the calls are paused, the calls waiting in the field continue with the new function once they resume
+					// (closures with the context set above)
+					if (!InternalCallPauses.TryRetarget(field, function))
+						field.SetValue(null, function);
+				}
+				catch (Exception ex)
 				{
-					LogMessage($"Cannot register internal '{name}', failed to find it in type '{containingTypeName}'", MessageLevel.Error);
-					continue;
+					Fail(InternalCallErrorReason.Exception, $"Cannot register internal call '{name}', binding it threw {ex.GetType().Name}: {ex.Message}");
 				}
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+			errors.Add(("", "", InternalCallErrorReason.Exception, $"Cannot register internal calls, {ex.GetType().Name}: {ex.Message}"));
+		}
+
+		return errors.Count;
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetLastInternalCallErrors(NativeInternalCallError* OutErrors, int* OutErrorCount)
+	{
+		try
+		{
+			var errors = s_LastErrors;
+			*OutErrorCount = errors?.Count ?? 0;
 
-				if (!field.FieldType.IsFunctionPointer)
+			if (OutErrors == null || errors == null)
+				return;
+
+			for (int i = 0; i < errors.Count; i++)
+			{
+				OutErrors[i] = new NativeInternalCallError
 				{
-					LogMessage($"Field '{name}' is not a function pointer type!", MessageLevel.Error);
+					ClassName = errors[i].ClassName,
+					FieldName = errors[i].FieldName,
+					Reason = errors[i].Reason,
+					Message = errors[i].Message
+				};
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	/// <summary>
+	/// Finds the static function pointer fields of the assembly that are still null, and fills them with a stub
+	/// that throws InternalCallNotBoundException if InBindStubs is set.
+	/// Returns the number of unbound fields, see GetLastUnboundInternalCalls.
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static int AuditInternalCalls(int InAssemblyId, Bool32 InBindStubs)
+	{
+		var unboundCalls = s_LastUnboundCalls ??= new();
+		unboundCalls.Clear();
+
+		try
+		{
+			if (!AssemblyLoader.TryGetAssembly(InAssemblyId, out var assembly) || assembly == null)
+			{
+				LogMessage($"Couldn't audit internal calls of assembly '{InAssemblyId}', assembly not found.", MessageLevel.Error);
+				return 0;
+			}
+
+			foreach (var field in GetFunctionPointerFields(assembly))
+			{
+				if (GetFunctionPointer(field) != IntPtr.Zero)
+					continue;
+
+				var className = field.DeclaringType!.FullName ?? field.DeclaringType.Name;
+				unboundCalls.Add((className, field.Name));
+
+				if (!InBindStubs)
 					continue;
+
+				try
+				{
+					field.SetValue(null, InternalCallStubs.CreateThrowing(assembly, field.FieldType, $"{className}.{field.Name}"));
 				}
+				catch (Exception ex)
+				{
+					LogMessage($"Couldn't create a stub for internal call '{className}.{field.Name}': {ex.Message}", MessageLevel.Error);
+				}
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
 
-				field.SetValue(null, internalCall.NativeFunctionPtr);
+		return unboundCalls.Count;
+	}
+
+	/// <summary>
+	/// Nulls every closure field of the assembly together with its context field, called when the native side frees the closures
+	/// </summary>
+	[UnmanagedCallersOnly]
+	internal static void UnbindInternalClosures(int InAssemblyId)
+	{
+		try
+		{
+			// NOTE: The assembly is already gone if its context was unloaded first, which is fine since nothing can call into it anymore
+			if (!AssemblyLoader.TryGetAssembly(InAssemblyId, out var assembly) || assembly == null)
+				return;
+
+			var bindingFlags = BindingFlags.Static | BindingFlags.Public | BindingFlags.NonPublic | BindingFlags.DeclaredOnly;
+
+			foreach (var field in GetFunctionPointerFields(assembly))
+			{
+				var contextField = field.DeclaringType!.GetField(field.Name + "Context", bindingFlags);
+
+				if (contextField == null || contextField.FieldType != typeof(IntPtr) || (IntPtr)contextField.GetValue(null)! == IntPtr.Zero)
+					continue;
+
+				field.SetValue(null, IntPtr.Zero);
+				contextField.SetValue(null, IntPtr.Zero);
 			}
 		}
 		catch (Exception ex)
@@ -72,4 +286,102 @@ internal static class InternalCallsManager
 			HandleException(ex);
 		}
 	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetLastUnboundInternalCalls(NativeUnboundInternalCall* OutUnboundCalls, int* OutUnboundCallCount)
+	{
+		try
+		{
+			var unboundCalls = s_LastUnboundCalls;
+			*OutUnboundCallCount = unboundCalls?.Count ?? 0;
+
+			if (OutUnboundCalls == null || unboundCalls == null)
+				return;
+
+			for (int i = 0; i < unboundCalls.Count; i++)
+			{
+				OutUnboundCalls[i] = new NativeUnboundInternalCall
+				{
+					ClassName = unboundCalls[i].ClassName,
+					FieldName = unboundCalls[i].FieldName
+				};
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	/// <summary>
+	/// Every static function pointer field in the assembly, which is what internal calls are bound to
+	/// </summary>
+	internal static IEnumerable<FieldInfo> GetFunctionPointerFields(Assembly InAssembly)
+	{
+		var bindingFlags = BindingFlags.Static | BindingFlags.Public | BindingFlags.NonPublic | BindingFlags.DeclaredOnly;
+
+		foreach (var type in InAssembly.GetTypes())
+		{
+			// NOTE: Open generic types have no static fields to read until they're constructed
+			if (type.ContainsGenericParameters)
+				continue;
+
+			foreach (var field in type.GetFields(bindingFlags))
+			{
+				if (field.FieldType.IsFunctionPointer && !field.IsLiteral)
+					yield return field;
+			}
+		}
+	}
+
+	internal static unsafe IntPtr GetFunctionPointer(FieldInfo InField)
+	{
+		return InField.GetValue(null) switch
+		{
+			IntPtr pointer => pointer,
+			Pointer pointer => (IntPtr)Pointer.Unbox(pointer),
+			_ => IntPtr.Zero
+		};
+	}
+
+	/// <summary>
+	/// Describes how the native function's signature differs from the function pointer field's, null if they match.
+	/// Structs are Unknown on the native side, so they match any struct the field declares but aren't checked further.
+	/// </summary>
+	private static string? ValidateSignature(Type InFunctionPointerType, InternalCall InInternalCall)
+	{
+		if (InInternalCall.ParameterCount < 0)
+			return null;
+
+		var parameterTypes = InFunctionPointerType.GetFunctionPointerParameterTypes();
+
+		if (parameterTypes.Length != InInternalCall.ParameterCount)
+			return $"the field takes {parameterTypes.Length} parameter(s) but the native function takes {InInternalCall.ParameterCount}.";
+
+		for (int i = 0; i < parameterTypes.Length; i++)
+		{
+			var nativeType = InInternalCall.GetParameterType(i);
+
+			if (nativeType != GetNativeManagedType(parameterTypes[i]))
+				return $"parameter {i} is '{parameterTypes[i]}' but the native function takes {nativeType}.";
+		}
+
+		var returnType = InFunctionPointerType.GetFunctionPointerReturnType();
+
+		if (InInternalCall.ReturnType != GetNativeManagedType(returnType))
+			return $"it returns '{returnType}' but the native function returns {InInternalCall.ReturnType}.";
+
+		return null;
+	}
+
+	/// <summary>
+	/// The type the native side declares for InType. A string can't cross as is, so only NativeString maps to String.
+	/// </summary>
+	private static ManagedType GetNativeManagedType(Type InType)
+	{
+		if (InType == typeof(string))
+			return ManagedType.Unknown;
+
+		return TypeInterface.GetManagedType(InType);
+	}
 }
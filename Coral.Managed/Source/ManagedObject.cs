@@ -1,6 +1,7 @@
 ﻿using Coral.Managed.Interop;
 
 using System;
+using System.Collections.Concurrent;
 using System.Collections.Generic;
 using System.Diagnostics;
 using System.Diagnostics.CodeAnalysis;
@@ -29,10 +30,13 @@ internal enum ManagedType
 	Double,
 
 	Bool,
+	Bool32,
 
 	String,
 
-	Pointer
+	Pointer,
+
+	Void
 };
 
 internal static class ManagedObject
@@ -87,7 +91,8 @@ internal static class ManagedObject
 		}
 	}
 
-	internal static Dictionary<MethodKey, MethodInfo> s_CachedMethods = new Dictionary<MethodKey, MethodInfo>();
+	// NOTE: Concurrent since contexts can be unloaded while a worker thread loads an assembly
+	internal static readonly ConcurrentDictionary<MethodKey, MethodInfo> s_CachedMethods = new();
 
 	[UnmanagedCallersOnly]
 	internal static unsafe IntPtr CreateObject(int InTypeID, Bool32 InWeakRef, IntPtr InParameters, ManagedType* InParameterTypes, int InParameterCount)
@@ -158,7 +163,9 @@ internal static class ManagedObject
 	{
 		try
 		{
-			GCHandle.FromIntPtr(InObjectHandle).Free();
+			var handle = GCHandle.FromIntPtr(InObjectHandle);
+			AssemblyLoader.UnregisterHandle(handle);
+			handle.Free();
 		}
 		catch (Exception ex)
 		{
@@ -202,7 +209,7 @@ internal static class ManagedObject
 				return null;
 			}
 
-			s_CachedMethods.Add(methodKey, methodInfo);
+			s_CachedMethods.TryAdd(methodKey, methodInfo);
 		}
 
 		return methodInfo;
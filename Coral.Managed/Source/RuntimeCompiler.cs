@@ -0,0 +1,289 @@
+using Coral.Managed.Interop;
+
+using System;
+using System.Collections;
+using System.Collections.Generic;
+using System.IO;
+using System.Linq;
+using System.Reflection;
+using System.Runtime.InteropServices;
+using System.Runtime.Loader;
+using System.Text;
+
+namespace Coral.Managed;
+
+using static ManagedHost;
+
+// NOTE: Matches Microsoft.CodeAnalysis.DiagnosticSeverity
+internal enum DiagnosticSeverity { Hidden, Info, Warning, Error }
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct CompilerDiagnostic
+{
+	public NativeString FilePath;
+	public int Line;
+	public int Column;
+	public DiagnosticSeverity Severity;
+	public NativeString Code;
+	public NativeString Message;
+}
+
+internal readonly record struct CompilerMessage(string FilePath, int Line, int Column, DiagnosticSeverity Severity, string Code, string Message);
+
+/// <summary>
+/// Compiles C# source with the Roslyn compiler that ships with the installed .NET SDK.
+/// Roslyn is loaded through reflection so Coral.Managed doesn't need a package reference to it.
+/// </summary>
+internal static class RuntimeCompiler
+{
+	private sealed class CompilerLoadContext : AssemblyLoadContext
+	{
+		private readonly string m_CompilerDirectory;
+
+		public CompilerLoadContext(string InCompilerDirectory)
+			: base("Coral.Compiler")
+		{
+			m_CompilerDirectory = InCompilerDirectory;
+		}
+
+		protected override Assembly? Load(AssemblyName InAssemblyName)
+		{
+			var assemblyPath = Path.Combine(m_CompilerDirectory, $"{InAssemblyName.Name}.dll");
+			return File.Exists(assemblyPath) ? LoadFromAssemblyPath(assemblyPath) : null;
+		}
+	}
+
+	private static Assembly? s_CodeAnalysisAssembly;
+	private static Assembly? s_CSharpCodeAnalysisAssembly;
+
+	internal static readonly List<CompilerMessage> s_LastDiagnostics = new();
+
+	private static string? FindCompilerDirectory()
+	{
+		var dotnetRoot = Environment.GetEnvironmentVariable("DOTNET_ROOT");
+
+		if (string.IsNullOrEmpty(dotnetRoot))
+		{
+			// NOTE: The runtime lives in <dotnet root>/shared/Microsoft.NETCore.App/<version>/
+			dotnetRoot = Path.GetFullPath(Path.Combine(RuntimeEnvironment.GetRuntimeDirectory(), "..", "..", ".."));
+		}
+
+		var sdkDirectory = Path.Combine(dotnetRoot, "sdk");
+
+		if (!Directory.Exists(sdkDirectory))
+			return null;
+
+		static Version ParseSdkVersion(string InDirectory)
+		{
+			var versionString = Path.GetFileName(InDirectory).Split('-')[0];
+			return Version.TryParse(versionString, out var version) ? version : new Version();
+		}
+
+		return Directory.GetDirectories(sdkDirectory)
+			.OrderByDescending(ParseSdkVersion)
+			.Select(directory => Path.Combine(directory, "Roslyn", "bincore"))
+			.FirstOrDefault(directory => File.Exists(Path.Combine(directory, "Microsoft.CodeAnalysis.CSharp.dll")));
+	}
+
+	private static bool LoadCompiler()
+	{
+		if (s_CSharpCodeAnalysisAssembly != null)
+			return true;
+
+		var compilerDirectory = FindCompilerDirectory();
+
+		if (compilerDirectory == null)
+		{
+			LogMessage("Couldn't find the Roslyn compiler, make sure a .NET SDK is installed.", MessageLevel.Error);
+			return false;
+		}
+
+		var compilerLoadContext = new CompilerLoadContext(compilerDirectory);
+		s_CodeAnalysisAssembly = compilerLoadContext.LoadFromAssemblyPath(Path.Combine(compilerDirectory, "Microsoft.CodeAnalysis.dll"));
+		s_CSharpCodeAnalysisAssembly = compilerLoadContext.LoadFromAssemblyPath(Path.Combine(compilerDirectory, "Microsoft.CodeAnalysis.CSharp.dll"));
+		return true;
+	}
+
+	private static Type GetCompilerType(Assembly InAssembly, string InTypeName)
+	{
+		return InAssembly.GetType(InTypeName, true)!;
+	}
+
+	// NOTE: Roslyn's API is made up of methods with a lot of optional parameters, so we pass the ones we care about by name
+	private static object?[] BindArguments(MethodBase InMethod, params (string Name, object? Value)[] InArguments)
+	{
+		var parameters = InMethod.GetParameters();
+		var arguments = new object?[parameters.Length];
+
+		for (int i = 0; i < parameters.Length; i++)
+		{
+			var parameter = parameters[i];
+			var argumentIndex = Array.FindIndex(InArguments, argument => argument.Name == parameter.Name);
+
+			if (argumentIndex >= 0)
+				arguments[i] = InArguments[argumentIndex].Value;
+			else if (parameter.HasDefaultValue)
+				arguments[i] = parameter.DefaultValue;
+			else
+				arguments[i] = parameter.ParameterType.IsValueType ? Activator.CreateInstance(parameter.ParameterType) : null;
+		}
+
+		return arguments;
+	}
+
+	private static T FindOverload<T>(IEnumerable<T> InCandidates, params string[] InRequiredParameters) where T : MethodBase
+	{
+		return InCandidates
+			.Where(candidate => InRequiredParameters.All(name => candidate.GetParameters().Any(parameter => parameter.Name == name)))
+			.Where(candidate => candidate.GetCustomAttribute<ObsoleteAttribute>() == null)
+			.OrderBy(candidate => candidate.GetParameters().Length)
+			.First();
+	}
+
+	private static object? GetProperty(object InTarget, string InName)
+	{
+		return InTarget.GetType().GetProperty(InName)!.GetValue(InTarget);
+	}
+
+	private static Array CreateTypedArray(Type InElementType, IList<object> InValues)
+	{
+		var array = Array.CreateInstance(InElementType, InValues.Count);
+
+		for (int i = 0; i < InValues.Count; i++)
+			array.SetValue(InValues[i], i);
+
+		return array;
+	}
+
+	/// <summary>
+	/// Returns the compiled assembly and its portable PDB, or null if the compilation failed.
+	/// Diagnostics from the last compilation (including warnings) are stored in s_LastDiagnostics.
+	/// </summary>
+	internal static (byte[] Assembly, byte[] Symbols)? Compile(string InAssemblyName, IReadOnlyList<(string Path, string Code)> InSources, IEnumerable<string> InReferences, out bool OutCompilerFound)
+	{
+		s_LastDiagnostics.Clear();
+		OutCompilerFound = LoadCompiler();
+
+		if (!OutCompilerFound)
+			return null;
+
+		var codeAnalysis = s_CodeAnalysisAssembly!;
+		var csharpCodeAnalysis = s_CSharpCodeAnalysisAssembly!;
+
+		var syntaxTreeType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.SyntaxTree");
+		var metadataReferenceType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.MetadataReference");
+		var outputKindType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.OutputKind");
+		var optimizationLevelType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.OptimizationLevel");
+		var emitOptionsType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.Emit.EmitOptions");
+		var debugInformationFormatType = GetCompilerType(codeAnalysis, "Microsoft.CodeAnalysis.Emit.DebugInformationFormat");
+		var csharpSyntaxTreeType = GetCompilerType(csharpCodeAnalysis, "Microsoft.CodeAnalysis.CSharp.CSharpSyntaxTree");
+		var csharpCompilationType = GetCompilerType(csharpCodeAnalysis, "Microsoft.CodeAnalysis.CSharp.CSharpCompilation");
+		var csharpCompilationOptionsType = GetCompilerType(csharpCodeAnalysis, "Microsoft.CodeAnalysis.CSharp.CSharpCompilationOptions");
+
+		var parseText = FindOverload(csharpSyntaxTreeType.GetMethods(BindingFlags.Public | BindingFlags.Static)
+			.Where(method => method.Name == "ParseText" && method.GetParameters()[0].ParameterType == typeof(string)), "text", "path", "encoding");
+
+		var syntaxTrees = InSources
+			.Select(source => parseText.Invoke(null, BindArguments(parseText, ("text", source.Code), ("path", source.Path), ("encoding", Encoding.UTF8)))!)
+			.ToList();
+
+		var createFromFile = FindOverload(metadataReferenceType.GetMethods(BindingFlags.Public | BindingFlags.Static)
+			.Where(method => method.Name == "CreateFromFile"), "path");
+
+		var trustedPlatformAssemblies = (AppContext.GetData("TRUSTED_PLATFORM_ASSEMBLIES") as string ?? "")
+			.Split(Path.PathSeparator, StringSplitOptions.RemoveEmptyEntries);
+
+		var references = trustedPlatformAssemblies
+			.Concat(InReferences)
+			.Distinct()
+			.Select(reference => createFromFile.Invoke(null, BindArguments(createFromFile, ("path", reference)))!)
+			.ToList();
+
+		var compilationOptionsConstructor = FindOverload(csharpCompilationOptionsType.GetConstructors(), "outputKind", "allowUnsafe", "optimizationLevel");
+		var compilationOptions = compilationOptionsConstructor.Invoke(BindArguments(compilationOptionsConstructor,
+			("outputKind", Enum.Parse(outputKindType, "DynamicallyLinkedLibrary")),
+			("allowUnsafe", true),
+			("optimizationLevel", Enum.Parse(optimizationLevelType, "Debug"))
+		));
+
+		var create = FindOverload(csharpCompilationType.GetMethods(BindingFlags.Public | BindingFlags.Static)
+			.Where(method => method.Name == "Create"), "assemblyName", "syntaxTrees", "references", "options");
+
+		var compilation = create.Invoke(null, BindArguments(create,
+			("assemblyName", InAssemblyName),
+			("syntaxTrees", CreateTypedArray(syntaxTreeType, syntaxTrees)),
+			("references", CreateTypedArray(metadataReferenceType, references)),
+			("options", compilationOptions)
+		))!;
+
+		var emitOptionsConstructor = FindOverload(emitOptionsType.GetConstructors(), "debugInformationFormat");
+		var emitOptions = emitOptionsConstructor.Invoke(BindArguments(emitOptionsConstructor,
+			("debugInformationFormat", Enum.Parse(debugInformationFormatType, "PortablePdb"))
+		));
+
+		var emit = FindOverload(compilation.GetType().GetMethods(BindingFlags.Public | BindingFlags.Instance)
+			.Where(method => method.Name == "Emit" && method.GetParameters()[0].ParameterType == typeof(Stream)), "peStream", "pdbStream", "options");
+
+		using var assemblyStream = new MemoryStream();
+		using var symbolsStream = new MemoryStream();
+
+		var emitResult = emit.Invoke(compilation, BindArguments(emit,
+			("peStream", assemblyStream),
+			("pdbStream", symbolsStream),
+			("options", emitOptions)
+		))!;
+
+		foreach (var diagnostic in (IEnumerable)GetProperty(emitResult, "Diagnostics")!)
+		{
+			var location = GetProperty(diagnostic, "Location")!;
+			var lineSpan = location.GetType().GetMethod("GetLineSpan", Type.EmptyTypes)!.Invoke(location, null)!;
+			var startLinePosition = GetProperty(lineSpan, "StartLinePosition")!;
+			var getMessage = diagnostic.GetType().GetMethod("GetMessage", new[] { typeof(IFormatProvider) })!;
+
+			s_LastDiagnostics.Add(new CompilerMessage(
+				(string?)GetProperty(lineSpan, "Path") ?? "",
+				(int)GetProperty(startLinePosition, "Line")! + 1,
+				(int)GetProperty(startLinePosition, "Character")! + 1,
+				(DiagnosticSeverity)Convert.ToInt32(GetProperty(diagnostic, "Severity")),
+				(string)GetProperty(diagnostic, "Id")!,
+				(string)getMessage.Invoke(diagnostic, new object?[] { null })!
+			));
+		}
+
+		if (!(bool)GetProperty(emitResult, "Success")!)
+			return null;
+
+		return (assemblyStream.ToArray(), symbolsStream.ToArray());
+	}
+
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetLastCompilerDiagnostics(CompilerDiagnostic* OutDiagnostics, int* OutDiagnosticCount)
+	{
+		try
+		{
+			*OutDiagnosticCount = s_LastDiagnostics.Count;
+
+			if (OutDiagnostics == null)
+				return;
+
+			for (int i = 0; i < s_LastDiagnostics.Count; i++)
+			{
+				var diagnostic = s_LastDiagnostics[i];
+
+				OutDiagnostics[i] = new CompilerDiagnostic
+				{
+					FilePath = diagnostic.FilePath,
+					Line = diagnostic.Line,
+					Column = diagnostic.Column,
+					Severity = diagnostic.Severity,
+					Code = diagnostic.Code,
+					Message = diagnostic.Message
+				};
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+}
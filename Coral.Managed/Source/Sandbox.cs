@@ -0,0 +1,312 @@
+using Coral.Managed.Interop;
+
+using System;
+using System.Collections.Generic;
+using System.Collections.Immutable;
+using System.Linq;
+using System.Reflection;
+using System.Reflection.Emit;
+using System.Reflection.Metadata;
+using System.Reflection.Metadata.Ecma335;
+using System.Reflection.PortableExecutable;
+using System.Runtime.InteropServices;
+
+namespace Coral.Managed;
+
+[StructLayout(LayoutKind.Sequential)]
+internal struct NativeSandboxFinding
+{
+	public NativeString TypeName;
+	public NativeString MethodName;
+	public NativeString Reference;
+}
+
+/// <summary>
+/// A forbidden reference, Method is null if the type itself references it (e.g as its base type)
+/// </summary>
+internal readonly record struct SandboxFinding(string TypeName, string? MethodName, string Reference);
+
+public class SandboxViolationException : Exception
+{
+	internal IReadOnlyList<SandboxFinding> Findings { get; }
+
+	internal SandboxViolationException(IReadOnlyList<SandboxFinding> InFindings)
+		: base($"Assembly references {InFindings.Count} forbidden API(s), first one is '{InFindings[0].Reference}'")
+	{
+		Findings = InFindings;
+	}
+}
+
+/// <summary>
+/// Scans the metadata and IL of an assembly for references to APIs it isn't allowed to use.
+/// Entries are prefixes of a namespace, type or member name (e.g System.IO, System.Diagnostics.Process or System.Environment.Exit),
+/// an allowed entry takes precedence over a denied one.
+/// </summary>
+internal sealed class SandboxPolicy
+{
+	private sealed class TypeNameProvider : ISignatureTypeProvider<string, object?>
+	{
+		public static readonly TypeNameProvider Instance = new();
+
+		public string GetArrayType(string InElementType, ArrayShape InShape) => $"{InElementType}[]";
+		public string GetByReferenceType(string InElementType) => InElementType;
+		public string GetFunctionPointerType(MethodSignature<string> InSignature) => "method*";
+		// NOTE: The type arguments are checked separately, see ReferencedTypesProvider
+		public string GetGenericInstantiation(string InGenericType, ImmutableArray<string> InTypeArguments) => InGenericType;
+		public string GetGenericMethodParameter(object? InGenericContext, int InIndex) => $"!!{InIndex}";
+		public string GetGenericTypeParameter(object? InGenericContext, int InIndex) => $"!{InIndex}";
+		public string GetModifiedType(string InModifier, string InUnmodifiedType, bool InIsRequired) => InUnmodifiedType;
+		public string GetPinnedType(string InElementType) => InElementType;
+		public string GetPointerType(string InElementType) => $"{InElementType}*";
+		public string GetPrimitiveType(PrimitiveTypeCode InTypeCode) => $"System.{InTypeCode}";
+		public string GetSZArrayType(string InElementType) => $"{InElementType}[]";
+		public string GetTypeFromDefinition(MetadataReader InReader, TypeDefinitionHandle InHandle, byte InRawTypeKind) => GetTypeName(InReader, InHandle);
+		public string GetTypeFromReference(MetadataReader InReader, TypeReferenceHandle InHandle, byte InRawTypeKind) => GetTypeName(InReader, InHandle);
+		public string GetTypeFromSpecification(MetadataReader InReader, object? InGenericContext, TypeSpecificationHandle InHandle, byte InRawTypeKind) => GetTypeName(InReader, InHandle);
+	}
+
+	/// <summary>
+	/// Every type outside the assembly a signature references, so e.g List&lt;Process&gt; is checked for Process too
+	/// </summary>
+	private sealed class ReferencedTypesProvider : ISignatureTypeProvider<ImmutableArray<string>, object?>
+	{
+		public static readonly ReferencedTypesProvider Instance = new();
+
+		public ImmutableArray<string> GetArrayType(ImmutableArray<string> InElementType, ArrayShape InShape) => InElementType;
+		public ImmutableArray<string> GetByReferenceType(ImmutableArray<string> InElementType) => InElementType;
+		public ImmutableArray<string> GetFunctionPointerType(MethodSignature<ImmutableArray<string>> InSignature) => InSignature.ParameterTypes.Aggregate(InSignature.ReturnType, (types, parameterType) => types.AddRange(parameterType));
+		public ImmutableArray<string> GetGenericInstantiation(ImmutableArray<string> InGenericType, ImmutableArray<ImmutableArray<string>> InTypeArguments) => InTypeArguments.Aggregate(InGenericType, (types, typeArgument) => types.AddRange(typeArgument));
+		public ImmutableArray<string> GetGenericMethodParameter(object? InGenericContext, int InIndex) => ImmutableArray<string>.Empty;
+		public ImmutableArray<string> GetGenericTypeParameter(object? InGenericContext, int InIndex) => ImmutableArray<string>.Empty;
+		public ImmutableArray<string> GetModifiedType(ImmutableArray<string> InModifier, ImmutableArray<string> InUnmodifiedType, bool InIsRequired) => InUnmodifiedType;
+		public ImmutableArray<string> GetPinnedType(ImmutableArray<string> InElementType) => InElementType;
+		public ImmutableArray<string> GetPointerType(ImmutableArray<string> InElementType) => InElementType;
+		public ImmutableArray<string> GetPrimitiveType(PrimitiveTypeCode InTypeCode) => ImmutableArray<string>.Empty;
+		public ImmutableArray<string> GetSZArrayType(ImmutableArray<string> InElementType) => InElementType;
+		public ImmutableArray<string> GetTypeFromDefinition(MetadataReader InReader, TypeDefinitionHandle InHandle, byte InRawTypeKind) => ImmutableArray<string>.Empty;
+		public ImmutableArray<string> GetTypeFromReference(MetadataReader InReader, TypeReferenceHandle InHandle, byte InRawTypeKind) => ImmutableArray.Create(GetTypeName(InReader, InHandle));
+		public ImmutableArray<string> GetTypeFromSpecification(MetadataReader InReader, object? InGenericContext, TypeSpecificationHandle InHandle, byte InRawTypeKind) => InReader.GetTypeSpecification(InHandle).DecodeSignature(this, InGenericContext);
+	}
+
+	private static readonly Dictionary<short, OperandType> s_OperandTypes = typeof(OpCodes)
+		.GetFields(BindingFlags.Public | BindingFlags.Static)
+		.Select(field => (OpCode)field.GetValue(null)!)
+		.ToDictionary(opCode => opCode.Value, opCode => opCode.OperandType);
+
+	private readonly List<string> m_Denied;
+	private readonly List<string> m_Allowed;
+	private readonly bool m_DenyPInvoke;
+
+	public SandboxPolicy(List<string> InDenied, List<string> InAllowed, bool InDenyPInvoke)
+	{
+		m_Denied = InDenied;
+		m_Allowed = InAllowed;
+		m_DenyPInvoke = InDenyPInvoke;
+	}
+
+	/// <summary>
+	/// Throws a SandboxViolationException listing every forbidden reference in the assembly
+	/// </summary>
+	internal unsafe void Check(byte* InAssemblyData, int InAssemblyDataLength)
+	{
+		using var peReader = new PEReader(InAssemblyData, InAssemblyDataLength);
+		var reader = peReader.GetMetadataReader();
+		var findings = new List<SandboxFinding>();
+
+		foreach (var typeHandle in reader.TypeDefinitions)
+		{
+			var type = reader.GetTypeDefinition(typeHandle);
+			var typeName = GetTypeName(reader, typeHandle);
+
+			if (!type.BaseType.IsNil)
+				CheckReference(reader, type.BaseType, typeName, null, findings);
+
+			foreach (var interfaceHandle in type.GetInterfaceImplementations())
+				CheckReference(reader, reader.GetInterfaceImplementation(interfaceHandle).Interface, typeName, null, findings);
+
+			foreach (var methodHandle in type.GetMethods())
+			{
+				var method = reader.GetMethodDefinition(methodHandle);
+				var methodName = reader.GetString(method.Name);
+
+				if ((method.Attributes & MethodAttributes.PinvokeImpl) != 0)
+				{
+					if (m_DenyPInvoke)
+					{
+						var import = method.GetImport();
+						var moduleName = import.Module.IsNil ? "" : reader.GetString(reader.GetModuleReference(import.Module).Name);
+						findings.Add(new SandboxFinding(typeName, methodName, $"P/Invoke {moduleName}!{reader.GetString(import.Name)}"));
+					}
+
+					continue;
+				}
+
+				if (method.RelativeVirtualAddress == 0)
+					continue;
+
+				var ilReader = peReader.GetMethodBody(method.RelativeVirtualAddress).GetILReader();
+
+				while (ilReader.RemainingBytes > 0)
+				{
+					short opCode = ilReader.ReadByte();
+
+					if (opCode == 0xFE)
+						opCode = unchecked((short)(0xFE00 | ilReader.ReadByte()));
+
+					if (!s_OperandTypes.TryGetValue(opCode, out var operandType))
+					{
+						// NOTE: The rest of the method can't be decoded, so it's rejected instead of only checked up to here
+						findings.Add(new SandboxFinding(typeName, methodName, $"Unknown IL opcode 0x{opCode:X4}"));
+						break;
+					}
+
+					switch (operandType)
+					{
+						case OperandType.InlineField:
+						case OperandType.InlineMethod:
+						case OperandType.InlineTok:
+						case OperandType.InlineType:
+							CheckReference(reader, MetadataTokens.EntityHandle(ilReader.ReadInt32()), typeName, methodName, findings);
+							break;
+						case OperandType.InlineSwitch:
+							ilReader.Offset += ilReader.ReadInt32() * 4;
+							break;
+						default:
+							ilReader.Offset += GetOperandSize(operandType);
+							break;
+					}
+				}
+			}
+		}
+
+		if (findings.Count > 0)
+			throw new SandboxViolationException(findings);
+	}
+
+	private void CheckReference(MetadataReader InReader, EntityHandle InHandle, string InTypeName, string? InMethodName, List<SandboxFinding> InFindings)
+	{
+		var reference = GetReferencedName(InReader, InHandle);
+
+		if (reference != null)
+			AddFindingIfDenied(reference, InTypeName, InMethodName, InFindings);
+
+		foreach (var referencedType in GetReferencedTypes(InReader, InHandle))
+			AddFindingIfDenied(referencedType, InTypeName, InMethodName, InFindings);
+	}
+
+	private void AddFindingIfDenied(string InReference, string InTypeName, string? InMethodName, List<SandboxFinding> InFindings)
+	{
+		if (!IsDenied(InReference))
+			return;
+
+		var finding = new SandboxFinding(InTypeName, InMethodName, InReference);
+
+		if (!InFindings.Contains(finding))
+			InFindings.Add(finding);
+	}
+
+	private bool IsDenied(string InReference)
+	{
+		static bool Matches(string InReference, string InPrefix)
+		{
+			if (!InReference.StartsWith(InPrefix, StringComparison.Ordinal))
+				return false;
+
+			return InReference.Length == InPrefix.Length || InReference[InPrefix.Length] is '.' or '+';
+		}
+
+		return m_Denied.Any(prefix => Matches(InReference, prefix)) && !m_Allowed.Any(prefix => Matches(InReference, prefix));
+	}
+
+	private static int GetOperandSize(OperandType InOperandType) => InOperandType switch
+	{
+		OperandType.InlineNone => 0,
+		OperandType.ShortInlineBrTarget or OperandType.ShortInlineI or OperandType.ShortInlineVar => 1,
+		OperandType.InlineVar => 2,
+		OperandType.InlineI8 or OperandType.InlineR => 8,
+		_ => 4
+	};
+
+	/// <summary>
+	/// The full name of a referenced type or member, or null if it's defined in the assembly being checked
+	/// </summary>
+	private static string? GetReferencedName(MetadataReader InReader, EntityHandle InHandle)
+	{
+		switch (InHandle.Kind)
+		{
+			case HandleKind.TypeReference:
+			case HandleKind.TypeSpecification:
+				return GetTypeName(InReader, InHandle);
+			case HandleKind.MemberReference:
+			{
+				var memberReference = InReader.GetMemberReference((MemberReferenceHandle)InHandle);
+				var parent = memberReference.Parent;
+
+				if (parent.Kind != HandleKind.TypeReference && parent.Kind != HandleKind.TypeSpecification)
+					return null;
+
+				return $"{GetTypeName(InReader, parent)}.{InReader.GetString(memberReference.Name)}";
+			}
+			case HandleKind.MethodSpecification:
+				return GetReferencedName(InReader, InReader.GetMethodSpecification((MethodSpecificationHandle)InHandle).Method);
+			default:
+				return null;
+		}
+	}
+
+	/// <summary>
+	/// The types a generic instantiation (of a type, or of the type or method a member reference is on) references
+	/// </summary>
+	private static IEnumerable<string> GetReferencedTypes(MetadataReader InReader, EntityHandle InHandle)
+	{
+		switch (InHandle.Kind)
+		{
+			case HandleKind.TypeSpecification:
+				return InReader.GetTypeSpecification((TypeSpecificationHandle)InHandle).DecodeSignature(ReferencedTypesProvider.Instance, null);
+			case HandleKind.MemberReference:
+				return GetReferencedTypes(InReader, InReader.GetMemberReference((MemberReferenceHandle)InHandle).Parent);
+			case HandleKind.MethodSpecification:
+			{
+				var methodSpecification = InReader.GetMethodSpecification((MethodSpecificationHandle)InHandle);
+				var typeArguments = methodSpecification.DecodeSignature(ReferencedTypesProvider.Instance, null);
+
+				return typeArguments.SelectMany(typeArgument => typeArgument).Concat(GetReferencedTypes(InReader, methodSpecification.Method));
+			}
+			default:
+				return Enumerable.Empty<string>();
+		}
+	}
+
+	private static string GetTypeName(MetadataReader InReader, EntityHandle InHandle)
+	{
+		switch (InHandle.Kind)
+		{
+			case HandleKind.TypeDefinition:
+			{
+				var type = InReader.GetTypeDefinition((TypeDefinitionHandle)InHandle);
+				var name = InReader.GetString(type.Name);
+
+				if (type.IsNested)
+					return $"{GetTypeName(InReader, type.GetDeclaringType())}+{name}";
+
+				var typeNamespace = InReader.GetString(type.Namespace);
+				return typeNamespace.Length > 0 ? $"{typeNamespace}.{name}" : name;
+			}
+			case HandleKind.TypeReference:
+			{
+				var type = InReader.GetTypeReference((TypeReferenceHandle)InHandle);
+				var name = InReader.GetString(type.Name);
+
+				if (type.ResolutionScope.Kind == HandleKind.TypeReference)
+					return $"{GetTypeName(InReader, type.ResolutionScope)}+{name}";
+
+				var typeNamespace = InReader.GetString(type.Namespace);
+				return typeNamespace.Length > 0 ? $"{typeNamespace}.{name}" : name;
+			}
+			case HandleKind.TypeSpecification:
+				return InReader.GetTypeSpecification((TypeSpecificationHandle)InHandle).DecodeSignature(TypeNameProvider.Instance, null);
+			default:
+				return "";
+		}
+	}
+}
@@ -1,6 +1,7 @@
 ﻿using Coral.Managed.Interop;
 
 using System;
+using System.Collections.Concurrent;
 using System.Collections.Generic;
 using System.Collections.Immutable;
 using System.Diagnostics;
@@ -51,12 +52,23 @@ internal static class TypeInterface
 		{ typeof(ulong), ManagedType.ULong },
 		{ typeof(float), ManagedType.Float },
 		{ typeof(double), ManagedType.Double },
-		{ typeof(Bool32), ManagedType.Bool },
+		{ typeof(Bool32), ManagedType.Bool32 },
 		{ typeof(bool), ManagedType.Bool },
 		{ typeof(NativeString), ManagedType.String },
 		{ typeof(string), ManagedType.String },
 	};
 
+	internal static ManagedType GetManagedType(Type InType)
+	{
+		if (InType.IsPointer || InType == typeof(IntPtr))
+			return ManagedType.Pointer;
+
+		if (InType == typeof(void))
+			return ManagedType.Void;
+
+		return s_TypeConverters.TryGetValue(InType, out var managedType) ? managedType : ManagedType.Unknown;
+	}
+
 	internal static unsafe T? FindSuitableMethod<T>(string? InMethodName, ManagedType* InParameterTypes, int InParameterCount, ReadOnlySpan<T> InMethods) where T : MethodBase
 	{
 		if (InMethodName == null)
@@ -85,16 +97,7 @@ internal static class TypeInterface
 
 			for (int i = 0; i < methodParams.Length; i++)
 			{
-				ManagedType paramType;
-
-				if (methodParams[i].ParameterType.IsPointer || methodParams[i].ParameterType == typeof(IntPtr))
-				{
-					paramType = ManagedType.Pointer;
-				}
-				else if (!s_TypeConverters.TryGetValue(methodParams[i].ParameterType, out paramType))
-				{
-					paramType = ManagedType.Unknown;
-				}
+				var paramType = GetManagedType(methodParams[i].ParameterType);
 
 				if (paramType == InParameterTypes[i])
 				{
@@ -148,6 +151,139 @@ internal static class TypeInterface
 		}
 	}
 
+	[StructLayout(LayoutKind.Sequential)]
+	internal struct TypeIndexEntry
+	{
+		public int TypeId;
+		public NativeString Namespace;
+		public int BaseTypesOffset;
+		public int BaseTypeCount;
+		public int InterfacesOffset;
+		public int InterfaceCount;
+		public int AttributesOffset;
+		public int AttributeCount;
+	}
+
+	private sealed record AssemblyTypeIndex(TypeIndexEntry[] Entries, string[] Namespaces, int[] RelatedTypes);
+
+	// NOTE: Built by the count call and taken by the fill call, so both report the same counts
+	private static readonly ConcurrentDictionary<int, AssemblyTypeIndex> s_AssemblyTypeIndexes = new();
+
+	// NOTE: Every id referenced by an entry (base types, interfaces and attribute types) is stored in OutRelatedTypes,
+	//		 the entries only store offsets into it. Pass null arrays to query the counts, the arrays are filled up to
+	//		 the capacities and the counts are always the full counts.
+	[UnmanagedCallersOnly]
+	internal static unsafe void GetAssemblyTypeIndex(int InAssemblyId, TypeIndexEntry* OutEntries, int InEntryCapacity, int* OutEntryCount, int* OutRelatedTypes, int InRelatedTypeCapacity, int* OutRelatedTypeCount)
+	{
+		try
+		{
+			AssemblyTypeIndex? index;
+
+			if (OutEntries == null && OutRelatedTypes == null)
+			{
+				index = BuildAssemblyTypeIndex(InAssemblyId);
+
+				if (index != null)
+					s_AssemblyTypeIndexes[InAssemblyId] = index;
+			}
+			else if (!s_AssemblyTypeIndexes.TryRemove(InAssemblyId, out index))
+			{
+				index = BuildAssemblyTypeIndex(InAssemblyId);
+			}
+
+			if (index == null)
+				return;
+
+			if (OutEntryCount != null)
+				*OutEntryCount = index.Entries.Length;
+
+			if (OutRelatedTypeCount != null)
+				*OutRelatedTypeCount = index.RelatedTypes.Length;
+
+			if (OutEntries != null)
+			{
+				for (int i = 0; i < Math.Min(index.Entries.Length, InEntryCapacity); i++)
+				{
+					var entry = index.Entries[i];
+					entry.Namespace = index.Namespaces[i];
+					OutEntries[i] = entry;
+				}
+			}
+
+			if (OutRelatedTypes != null)
+			{
+				for (int i = 0; i < Math.Min(index.RelatedTypes.Length, InRelatedTypeCapacity); i++)
+					OutRelatedTypes[i] = index.RelatedTypes[i];
+			}
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
+
+	private static AssemblyTypeIndex? BuildAssemblyTypeIndex(int InAssemblyId)
+	{
+		if (!AssemblyLoader.TryGetAssembly(InAssemblyId, out var assembly) || assembly == null)
+		{
+			LogMessage($"Couldn't build type index for assembly '{InAssemblyId}', assembly not found.", MessageLevel.Error);
+			return null;
+		}
+
+		var assemblyTypes = assembly.GetTypes();
+		var entries = new TypeIndexEntry[assemblyTypes.Length];
+		var namespaces = new string[assemblyTypes.Length];
+		var relatedTypes = new List<int>();
+
+		for (int i = 0; i < assemblyTypes.Length; i++)
+		{
+			var type = assemblyTypes[i];
+			var entry = new TypeIndexEntry { TypeId = s_CachedTypes.Add(type) };
+
+			entry.BaseTypesOffset = relatedTypes.Count;
+			for (var baseType = type.BaseType; baseType != null; baseType = baseType.BaseType)
+				relatedTypes.Add(s_CachedTypes.Add(baseType));
+			entry.BaseTypeCount = relatedTypes.Count - entry.BaseTypesOffset;
+
+			entry.InterfacesOffset = relatedTypes.Count;
+			foreach (var interfaceType in type.GetInterfaces())
+				relatedTypes.Add(s_CachedTypes.Add(interfaceType));
+			entry.InterfaceCount = relatedTypes.Count - entry.InterfacesOffset;
+
+			entry.AttributesOffset = relatedTypes.Count;
+			foreach (var attributeType in GetAttributeTypes(type))
+				relatedTypes.Add(s_CachedTypes.Add(attributeType));
+			entry.AttributeCount = relatedTypes.Count - entry.AttributesOffset;
+
+			entries[i] = entry;
+			namespaces[i] = type.Namespace ?? "";
+		}
+
+		return new AssemblyTypeIndex(entries, namespaces, relatedTypes.ToArray());
+	}
+
+	/// <summary>
+	/// The attribute types of InType including inherited ones, like GetCustomAttributes(true) but read from the metadata,
+	/// so the constructors of the attributes don't run
+	/// </summary>
+	private static HashSet<Type> GetAttributeTypes(Type InType)
+	{
+		var attributeTypes = new HashSet<Type>();
+
+		for (var type = InType; type != null; type = type.BaseType)
+		{
+			foreach (var attributeData in type.GetCustomAttributesData())
+			{
+				var attributeType = attributeData.AttributeType;
+
+				if (type == InType || (attributeType.GetCustomAttribute<AttributeUsageAttribute>()?.Inherited ?? true))
+					attributeTypes.Add(attributeType);
+			}
+		}
+
+		return attributeTypes;
+	}
+
 	[UnmanagedCallersOnly]
 	internal static unsafe void GetTypeId(NativeString InName, int* OutType)
 	{
@@ -836,4 +972,17 @@ internal static class TypeInterface
 			HandleException(ex);
 		}
 	}
+
+	[UnmanagedCallersOnly]
+	internal static void DestroyAttribute(int InAttribute)
+	{
+		try
+		{
+			s_CachedAttributes.Remove(InAttribute);
+		}
+		catch (Exception ex)
+		{
+			HandleException(ex);
+		}
+	}
 }
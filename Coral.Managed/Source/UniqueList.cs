@@ -30,6 +30,20 @@ public class UniqueIdList<T>
 		return m_Objects.TryGetValue(id, out obj);
 	}
 
+	public bool Remove(int id)
+	{
+		return m_Objects.TryRemove(id, out _);
+	}
+
+	public void RemoveWhere(Func<T, bool> InPredicate)
+	{
+		foreach (var (id, obj) in m_Objects)
+		{
+			if (InPredicate(obj))
+				m_Objects.TryRemove(id, out _);
+		}
+	}
+
 	public void Clear()
 	{
 		m_Objects.Clear();
@@ -20,7 +20,7 @@ namespace Example.Managed
 			public float Z;
 		}
 
-		internal static unsafe delegate*<float, float> TestInternalCall;
+		internal static unsafe delegate* unmanaged<float, float> TestInternalCall;
 
 		private int myPrivateValue;
 		public int PublicProp
@@ -1,20 +1,24 @@
 use sharpen::{
     TypeCacheError, TypeFns,
-    assembly::{AssemblyLoadError, ManagedAssembly},
+    assembly::AssemblyLoadError,
     host_instance::{CoralInitError, HostInstance, HostSettings},
+    internal_call::{InternalCallError, InternalCallRegistry},
     managed_object::ManagedObjectFns,
     message_level::MessageLevel,
     meta_info::Attribute,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 #[allow(unused)]
 enum ExampleError {
     CoralInitError(CoralInitError),
     AssemblyLoadError(AssemblyLoadError),
     TypeCacheError(TypeCacheError),
+    InternalCallErrors(Vec<InternalCallError>),
+    Error(sharpen::Error),
 }
 
+#[sharpen::internal_call(class = "Example.Managed.ExampleClass")]
 fn test_internal_call(value: f32) -> f32 {
     println!("Value in Icall: {value}");
 
@@ -46,38 +50,37 @@ fn main() -> Result<(), ExampleError> {
 
     let mut assembly_load_context = host_instance.create_assembly_load_context("ExampleContext");
 
+    let mut internal_calls = InternalCallRegistry::new();
+    internal_calls.register(&TEST_INTERNAL_CALL);
+    assembly_load_context.set_internal_calls(internal_calls);
+
     let assembly_path =
         std::path::PathBuf::from("./Example.Managed/bin/Debug/net8.0/Example.Managed.dll");
     let assembly = assembly_load_context
         .load_assembly(&assembly_path)
         .map_err(|err| ExampleError::AssemblyLoadError(err))?;
 
-    unsafe {
-        // TODO: Mutability
-        (assembly.as_ref() as *const _ as *mut ManagedAssembly)
-            .as_mut()
-            .unwrap()
-            .add_internal_call(
-                "Example.Managed.ExampleClass",
-                "TestInternalCall",
-                test_internal_call as _,
-            );
+    if !assembly.internal_call_errors().is_empty() {
+        return Err(ExampleError::InternalCallErrors(
+            assembly.internal_call_errors().to_vec(),
+        ));
     }
-    assembly.upload_internal_calls();
 
     let example_type = assembly
         .get_type("Example.Managed.ExampleClass")
         .map_err(|err| ExampleError::TypeCacheError(err))?;
 
     // TODO: Safety of specifying wrong return type or argument type?
-    let value = example_type.invoke_static_method::<f32>("StaticMethod", (50.0f32,));
+    let value = example_type
+        .invoke_static_method::<f32>("StaticMethod", (50.0f32,))
+        .map_err(|err| ExampleError::Error(err))?;
     println!("Value in rust: {value}");
 
     let custom_attribute_type = assembly
         .get_type("Example.Managed.CustomAttribute")
         .map_err(|err| ExampleError::TypeCacheError(err))?;
 
-    for attribute in &example_type.get_attributes() {
+    for attribute in &example_type.get_attributes().map_err(ExampleError::Error)? {
         // TODO: Mutability
         if *unsafe {
             (&*attribute as *const _ as *mut Attribute)
@@ -94,27 +97,39 @@ fn main() -> Result<(), ExampleError> {
         }
     }
 
-    let example_instance = example_type.create_instance((50i32,));
-    example_instance.invoke_method::<()>(
-        "Void MemberMethod(MyVec3)",
-        (MyVec3 {
-            x: 10.0,
-            y: 10.0,
-            z: 10.0,
-        },),
-    );
-
-    example_instance.set_property_value("PublicProp", 10i32);
+    let example_instance = example_type
+        .create_instance((50i32,))
+        .map_err(|err| ExampleError::Error(err))?;
+    example_instance
+        .invoke_method::<()>(
+            "Void MemberMethod(MyVec3)",
+            (MyVec3 {
+                x: 10.0,
+                y: 10.0,
+                z: 10.0,
+            },),
+        )
+        .map_err(|err| ExampleError::Error(err))?;
+
+    example_instance
+        .set_property_value("PublicProp", 10i32)
+        .map_err(|err| ExampleError::Error(err))?;
     // TODO: Remove the need for _, in generic
     println!(
         "PublicProp: {}",
-        example_instance.get_property_value::<_, i32>("PublicProp")
+        example_instance
+            .get_property_value::<_, i32>("PublicProp")
+            .map_err(|err| ExampleError::Error(err))?
     );
 
-    example_instance.set_field_value("myPrivateValue", 10i32);
+    example_instance
+        .set_field_value("myPrivateValue", 10i32)
+        .map_err(|err| ExampleError::Error(err))?;
     println!(
         "myPrivateValue: {}",
-        example_instance.get_field_value::<_, i32>("myPrivateValue")
+        example_instance
+            .get_field_value::<_, i32>("myPrivateValue")
+            .map_err(|err| ExampleError::Error(err))?
     );
 
     // TODO: Arrays and maybe rename CSharpNativeString
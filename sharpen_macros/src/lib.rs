@@ -0,0 +1,373 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    FnArg, ItemFn, Lifetime, LitStr, ReturnType, Type, parse_macro_input, parse_quote,
+    spanned::Spanned, visit_mut::VisitMut,
+};
+
+/// Turns a plain Rust function into an internal call that C# can call through a `delegate*` field.
+///
+/// Generates an `extern "system"` shim that converts the arguments and return value (see `InternalCallArg` and
+/// `InternalCallReturn`), and a `const` named after the function in upper case (e.g `TEST_INTERNAL_CALL`) that is passed
+/// to `ManagedAssembly::register_internal_call` or `InternalCallRegistry::register`. The function itself is left untouched.
+///
+/// A function returning a `Result` (spelled `Result`, e.g `io::Result<T>` works but other aliases don't) throws its `Err`
+/// in C# as an `InternalCallFailedException`, see `InternalCallResult`.
+///
+/// `class` is the full name of the C# class that declares the field, `field` defaults to the function name in PascalCase:
+/// ```ignore
+/// #[sharpen::internal_call(class = "MyGame.Native")]
+/// fn log_message(message: &str, is_error: bool) { ... }
+/// ```
+#[proc_macro_attribute]
+pub fn internal_call(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut class = None;
+    let mut field = None;
+    let attribute_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("class") {
+            class = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else if meta.path.is_ident("field") {
+            field = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `class` or `field`"))
+        }
+    });
+    parse_macro_input!(attr with attribute_parser);
+
+    let function = parse_macro_input!(item as ItemFn);
+
+    match expand(function, class, field) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(
+    function: ItemFn,
+    class: Option<LitStr>,
+    field: Option<LitStr>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let signature = &function.sig;
+    let Some(class) = class else {
+        return Err(syn::Error::new(
+            signature.span(),
+            "missing the C# class, e.g #[internal_call(class = \"MyGame.Native\")]",
+        ));
+    };
+    // NOTE: Lifetime parameters are fine, they don't change the native signature
+    let generics = &signature.generics;
+    if generics.type_params().next().is_some()
+        || generics.const_params().next().is_some()
+        || signature.asyncness.is_some()
+    {
+        return Err(syn::Error::new(
+            signature.span(),
+            "internal calls can't be generic or async",
+        ));
+    }
+
+    let name = &signature.ident;
+    let field =
+        field.unwrap_or_else(|| LitStr::new(&to_pascal_case(&name.to_string()), name.span()));
+    let shim_name = format_ident!("__sharpen_internal_call_{}", name);
+    let registration_name = format_ident!("{}", name.to_string().to_uppercase());
+    let host_name = format_ident!("__SHARPEN_INTERNAL_CALL_HOST_{}", registration_name);
+
+    let mut shim_parameters = vec![];
+    let mut shim_parameter_types = vec![];
+    let mut conversions = vec![];
+    let mut arguments = vec![];
+
+    for (i, input) in signature.inputs.iter().enumerate() {
+        let FnArg::Typed(input) = input else {
+            return Err(syn::Error::new(
+                input.span(),
+                "internal calls can't take `self`",
+            ));
+        };
+
+        let argument = format_ident!("arg{}", i);
+        let r#type = &input.ty;
+
+        // NOTE: &str can't borrow from the native string, so it's converted to a String that is borrowed instead
+        let (native_type, conversion, call_argument) = if is_str_reference(r#type) {
+            (
+                quote!(
+                    <::std::string::String as ::sharpen::internal_call::InternalCallArg>::Native
+                ),
+                quote! {
+                    let #argument: ::std::string::String =
+                        ::sharpen::internal_call::InternalCallArg::from_native(#argument, &#host_name);
+                },
+                quote!(&#argument),
+            )
+        } else {
+            let static_type = with_static_lifetimes(r#type);
+            let inferred_type = with_inferred_lifetimes(r#type);
+            (
+                quote!(<#static_type as ::sharpen::internal_call::InternalCallArg>::Native),
+                quote! {
+                    let #argument = <#inferred_type as ::sharpen::internal_call::InternalCallArg>::from_native(#argument, &#host_name);
+                },
+                quote!(#argument),
+            )
+        };
+
+        shim_parameters.push(quote!(#argument: #native_type));
+        shim_parameter_types.push(native_type);
+        conversions.push(conversion);
+        arguments.push(call_argument);
+    }
+
+    let return_type: Type = match &signature.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, r#type) => with_static_lifetimes(r#type),
+    };
+
+    // NOTE: Errors are passed back through an extra parameter that C# doesn't declare, see `InternalCallSignature::of_fallible`
+    let (native_return_type, return_conversion, signature_constructor) = if is_result(&return_type)
+    {
+        shim_parameters.push(quote!(
+            __sharpen_error: *mut ::sharpen::string::CSharpNativeString
+        ));
+        shim_parameter_types.push(quote!(*mut ::sharpen::string::CSharpNativeString));
+
+        (
+            quote!(<#return_type as ::sharpen::internal_call::InternalCallResult>::Native),
+            // SAFETY: C# passes a pointer to the error local of the stub it calls the function through
+            quote!({
+                let result = #name(#(#arguments),*);
+                unsafe { ::sharpen::internal_call::InternalCallResult::into_native(result, __sharpen_error) }
+            }),
+            quote!(of_fallible),
+        )
+    } else {
+        (
+            quote!(<#return_type as ::sharpen::internal_call::InternalCallReturn>::Native),
+            quote!(::sharpen::internal_call::InternalCallReturn::into_native(#name(#(#arguments),*))),
+            quote!(of),
+        )
+    };
+
+    let visibility = &function.vis;
+
+    Ok(quote! {
+        #function
+
+        #[doc(hidden)]
+        static #host_name: ::sharpen::internal_call::InternalCallHost =
+            ::sharpen::internal_call::InternalCallHost::new();
+
+        #[doc(hidden)]
+        extern "system" fn #shim_name(#(#shim_parameters),*) -> #native_return_type {
+            #(#conversions)*
+            #return_conversion
+        }
+
+        #visibility const #registration_name: ::sharpen::internal_call::InternalCallRegistration =
+            ::sharpen::internal_call::InternalCallRegistration {
+                class_name: #class,
+                variable_name: #field,
+                function: || {
+                    ::sharpen::InternalCallFn::as_ptr(
+                        #shim_name as extern "system" fn(#(#shim_parameter_types),*) -> #native_return_type,
+                    )
+                },
+                signature: ::sharpen::internal_call::InternalCallSignature::#signature_constructor::<
+                    extern "system" fn(#(#shim_parameter_types),*) -> #native_return_type
+                >,
+                host: || &#host_name,
+            };
+    })
+}
+
+fn is_result(r#type: &Type) -> bool {
+    let Type::Path(path) = r#type else {
+        return false;
+    };
+
+    path.qself.is_none()
+        && path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Result")
+}
+
+fn is_str_reference(r#type: &Type) -> bool {
+    let Type::Reference(reference) = r#type else {
+        return false;
+    };
+
+    matches!(&*reference.elem, Type::Path(path) if path.qself.is_none() && path.path.is_ident("str"))
+}
+
+/// The native type of a parameter doesn't depend on its lifetimes, but the shim's fn pointer type can't have elided ones
+fn with_static_lifetimes(r#type: &Type) -> Type {
+    replace_lifetimes(r#type, "'static")
+}
+
+/// The function's own lifetime parameters aren't declared in the shim, so the conversions let them be inferred
+fn with_inferred_lifetimes(r#type: &Type) -> Type {
+    replace_lifetimes(r#type, "'_")
+}
+
+fn replace_lifetimes(r#type: &Type, replacement: &str) -> Type {
+    struct ReplaceLifetimes<'a>(&'a str);
+
+    impl VisitMut for ReplaceLifetimes<'_> {
+        fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+            reference.lifetime = Some(Lifetime::new(self.0, reference.and_token.span));
+            syn::visit_mut::visit_type_reference_mut(self, reference);
+        }
+
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            *lifetime = Lifetime::new(self.0, lifetime.span());
+        }
+    }
+
+    let mut r#type = r#type.clone();
+    ReplaceLifetimes(replacement).visit_type_mut(&mut r#type);
+
+    r#type
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+
+    use super::*;
+
+    fn type_string(r#type: &Type) -> String {
+        r#type.to_token_stream().to_string()
+    }
+
+    #[test]
+    fn pascal_case_field_names() {
+        assert_eq!(to_pascal_case("log_message"), "LogMessage");
+        assert_eq!(to_pascal_case("scale"), "Scale");
+        assert_eq!(
+            to_pascal_case("_leading__double_trailing_"),
+            "LeadingDoubleTrailing"
+        );
+        assert_eq!(to_pascal_case("get_2d_position"), "Get2dPosition");
+        assert_eq!(to_pascal_case("already_Pascal"), "AlreadyPascal");
+    }
+
+    #[test]
+    fn lifetimes_are_replaced() {
+        let elided: Type = parse_quote!(&[u8]);
+        assert_eq!(
+            type_string(&with_static_lifetimes(&elided)),
+            type_string(&parse_quote!(&'static [u8]))
+        );
+
+        let named: Type = parse_quote!(&'a [f32]);
+        assert_eq!(
+            type_string(&with_inferred_lifetimes(&named)),
+            type_string(&parse_quote!(&'_ [f32]))
+        );
+
+        let nested: Type = parse_quote!(Wrapper<'a, &'b mut &u8>);
+        assert_eq!(
+            type_string(&with_static_lifetimes(&nested)),
+            type_string(&parse_quote!(Wrapper<'static, &'static mut &'static u8>))
+        );
+
+        let owned: Type = parse_quote!(f32);
+        assert_eq!(
+            type_string(&with_static_lifetimes(&owned)),
+            type_string(&owned)
+        );
+    }
+
+    #[test]
+    fn str_references_are_detected() {
+        assert!(is_str_reference(&parse_quote!(&str)));
+        assert!(is_str_reference(&parse_quote!(&'a str)));
+        assert!(!is_str_reference(&parse_quote!(&String)));
+        assert!(!is_str_reference(&parse_quote!(str)));
+        assert!(!is_str_reference(&parse_quote!(&[u8])));
+    }
+
+    #[test]
+    fn generic_and_async_functions_are_rejected() {
+        let class = Some(LitStr::new("MyGame.Native", proc_macro2::Span::call_site()));
+
+        let generic: ItemFn = parse_quote!(
+            fn scale<T>(value: T) -> T {
+                value
+            }
+        );
+        assert!(expand(generic, class.clone(), None).is_err());
+
+        let asynchronous: ItemFn = parse_quote!(
+            async fn scale(value: f32) -> f32 {
+                value
+            }
+        );
+        assert!(expand(asynchronous, class.clone(), None).is_err());
+
+        let missing_class: ItemFn = parse_quote!(
+            fn scale(value: f32) -> f32 {
+                value
+            }
+        );
+        assert!(expand(missing_class, None, None).is_err());
+
+        let with_lifetime: ItemFn = parse_quote!(
+            fn first<'a>(values: &'a [f32]) -> f32 {
+                values[0]
+            }
+        );
+        assert!(expand(with_lifetime, class, None).is_ok());
+    }
+
+    #[test]
+    fn result_returns_are_detected() {
+        assert!(is_result(&parse_quote!(Result<f32, String>)));
+        assert!(is_result(&parse_quote!(std::io::Result<()>)));
+        assert!(!is_result(&parse_quote!(Option<f32>)));
+        assert!(!is_result(&parse_quote!(&Result<f32, String>)));
+    }
+
+    #[test]
+    fn result_returns_take_an_error_parameter() {
+        let class = Some(LitStr::new("MyGame.Native", proc_macro2::Span::call_site()));
+
+        let fallible: ItemFn = parse_quote!(
+            fn parse(text: &str) -> Result<f32, String> {
+                text.parse().map_err(|_| text.to_string())
+            }
+        );
+        let tokens = expand(fallible, class.clone(), None).unwrap().to_string();
+        assert!(
+            tokens.contains("__sharpen_error : * mut :: sharpen :: string :: CSharpNativeString")
+        );
+        assert!(tokens.contains("InternalCallSignature :: of_fallible"));
+
+        let infallible: ItemFn = parse_quote!(
+            fn scale(value: f32) -> f32 {
+                value
+            }
+        );
+        let tokens = expand(infallible, class, None).unwrap().to_string();
+        assert!(!tokens.contains("__sharpen_error"));
+        assert!(tokens.contains("InternalCallSignature :: of ::"));
+    }
+}
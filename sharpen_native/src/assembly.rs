@@ -1,44 +1,166 @@
-use std::{str::FromStr, sync::Arc};
-
-use netcorehost::pdcstring;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{
+        Arc, Condvar, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use crate::{
-    InternalCall, TypeId, coral_managed_fns::AssemblyLoadStatus, host_instance::HostInstance,
-    sharp_type::Type, string::CSharpNativeString, type_cache::TypeCacheError,
+    AsyncInternalClosure, InternalCallFn, InternalClosure,
+    compiler::{CSharpSource, CompileError, last_compiler_diagnostics},
+    coral_managed_fns::AssemblyLoadStatus,
+    host_instance::HostInstance,
+    initializer::{InitializerArgument, InitializerError, InitializerSettings, run_initializers},
+    internal_call::{
+        InternalCallError, InternalCallHostSlot, InternalCallRegistration, InternalCallRegistry,
+        InternalCallSignature, InternalCallTable, RegisteredInternalCall, UnboundInternalCall,
+        audit_internal_calls,
+    },
+    resolver::{
+        AssemblyName, AssemblyResolution, ResolverState, resolve_assembly_callback,
+        resolve_unmanaged_callback,
+    },
+    sandbox::{SandboxFinding, SandboxPolicy, last_sandbox_findings},
+    sharp_type::Type,
+    string::{CSharpNativeString, ScopedCSharpNativeString},
+    type_cache::TypeCacheError,
+    type_index::TypeIndex,
+    verification::{VerificationPolicy, VerificationState, verify_assembly_callback},
+    virtual_source::{VirtualAssemblySource, read_assembly},
 };
 
 pub struct ManagedAssembly {
     host: HostInstance,
+    /// Allocated by Coral.Managed for every load, so it never collides with another assembly (or another version of this one)
     assembly_id: i32,
     load_status: AssemblyLoadStatus,
     name: String,
-    internal_call_name_storage: Vec<pdcstring::PdCString>,
-    // TODO: Have a C# compatible InternalCall def and a proper rust one without actual pointers
-    internal_calls: Vec<InternalCall>,
-    types: Vec<Arc<Type>>,
+    internal_calls: InternalCallTable,
+    rebound_internal_calls: Mutex<Vec<ReboundInternalCalls>>,
+    pub(crate) types: Vec<Arc<Type>>,
+    type_index: TypeIndex,
+    initializer_errors: Vec<InitializerError>,
+    internal_call_errors: Vec<InternalCallError>,
 }
 
 impl ManagedAssembly {
-    pub fn new(
+    pub(crate) fn new(
         host: HostInstance,
         assembly_id: i32,
         load_status: AssemblyLoadStatus,
         name: String,
         types: Vec<Arc<Type>>,
+        type_index: TypeIndex,
     ) -> ManagedAssembly {
         Self {
             host,
             assembly_id,
             load_status,
             name,
-            internal_call_name_storage: vec![],
-            internal_calls: vec![],
+            internal_calls: InternalCallTable::default(),
+            rebound_internal_calls: Mutex::new(vec![]),
             types,
+            type_index,
+            initializer_errors: vec![],
+            internal_call_errors: vec![],
         }
     }
 
+    /// Checks the status of the load that produced `assembly_id` and caches all of its types
+    pub(crate) fn load(
+        host: &HostInstance,
+        assembly_id: i32,
+        context: Option<&ContextStamp>,
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        LoadedAssembly::load(host, assembly_id, context).map(|loaded| loaded.into_assembly(host))
+    }
+
+    /// Binds `function` to the static `delegate* unmanaged<...>` field `variable_name` of `class_name`. The signature
+    /// of `function` is checked against the field's when the calls are uploaded, a mismatching call is rejected
+    /// (and logged) instead of being bound.
+    pub fn add_internal_call<F: InternalCallFn>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        function: F,
+    ) {
+        self.internal_calls.push(
+            &self.name,
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: function.as_ptr(),
+                signature: Some(InternalCallSignature::of::<F>()),
+                closure: None,
+                host: None,
+            },
+        );
+    }
+
+    /// Binds `closure` to the static `delegate* unmanaged<IntPtr, ...>` field `variable_name` of `class_name`, for
+    /// calls that need state instead of globals. The class also needs a static `IntPtr` field named
+    /// `{variable_name}Context`, which C# passes back as the first argument:
+    /// ```cs
+    /// static delegate* unmanaged<IntPtr, float, float> Scale;
+    /// static IntPtr ScaleContext;
+    ///
+    /// public static float ScaleValue(float value) => Scale(ScaleContext, value);
+    /// ```
+    /// The closure lives as long as the assembly, both fields are set back to null when it's dropped.
+    pub fn add_internal_closure<Args, F: InternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        closure: F,
+    ) {
+        self.internal_calls.push(
+            &self.name,
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: F::trampoline(),
+                signature: Some(InternalCallSignature::of_closure::<Args, F>()),
+                closure: Some(Arc::new(closure)),
+                host: None,
+            },
+        );
+    }
+
+    /// Binds `closure`, which returns a future, to the static `delegate* unmanaged<IntPtr, ..., IntPtr, void>` field
+    /// `variable_name` of `class_name`, for calls that C# awaits as a `Task<T>` (e.g asset loading). Like
+    /// [`ManagedAssembly::add_internal_closure`] the class needs a `{variable_name}Context` field, and C# passes the
+    /// handle of a task created by `InternalCallTasks.Create` as the last argument:
+    /// ```cs
+    /// static delegate* unmanaged<IntPtr, NativeString, IntPtr, void> LoadText;
+    /// static IntPtr LoadTextContext;
+    ///
+    /// public static Task<string> LoadTextAsync(string path)
+    /// {
+    ///     var task = InternalCallTasks.Create<string>(out var handle);
+    ///     LoadText(LoadTextContext, path, handle);
+    ///     return task;
+    /// }
+    /// ```
+    /// The future is spawned on the executor set with [`HostInstance::set_internal_call_executor`], and completes the
+    /// task with its output (or fails it with an `InternalCallFailedException` if it's an `Err`).
+    pub fn add_async_internal_call<Args, F: AsyncInternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        closure: F,
+    ) {
+        self.internal_calls.push(
+            &self.name,
+            class_name,
+            variable_name,
+            RegisteredInternalCall::from_async(closure),
+        );
+    }
+
     /// ## Functionality
-    /// Adds the ability to call a rust function within the given class of the assembly
+    /// Adds the ability to call a rust function within the given class of the assembly, without validating its signature.
+    /// Prefer [`ManagedAssembly::add_internal_call`].
     ///
     /// ## Parameters
     /// `class_name`: Name of the class
@@ -52,38 +174,521 @@ impl ManagedAssembly {
     /// malformed, e.g. not a pointer to an `extern "system"` function. This function will also cause undefined behaviour if the rust function
     /// and the C# function declaration do not match.
     // TODO: Find a way to not use *const c_void
-    pub unsafe fn add_internal_call(
+    pub unsafe fn add_internal_call_unchecked(
         &mut self,
         class_name: &str,
         variable_name: &str,
         fn_ptr: *const unsafe extern "system" fn() -> (),
     ) {
-        let assembly_qualified_name = format!("{class_name}+{variable_name}, {}", self.name);
-        let name = pdcstring::PdCString::from_str(&assembly_qualified_name).unwrap();
-
-        self.internal_calls.push(InternalCall {
-            name: name.as_ptr(),
-            native_function_ptr: fn_ptr as _,
-        });
-        self.internal_call_name_storage.push(name);
+        self.internal_calls.push(
+            &self.name,
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: fn_ptr as _,
+                signature: None,
+                closure: None,
+                host: None,
+            },
+        );
     }
 
-    // TODO: Get Result<(), Err>
-    pub fn upload_internal_calls(&self) {
-        (self.host.managed_functions().set_internal_calls)(
-            self.internal_calls.as_ptr() as *mut _,
-            self.internal_calls.len() as i32,
+    /// Adds an internal call generated by [`crate::internal_call`], e.g `assembly.register_internal_call(&LOG_MESSAGE)`
+    pub fn register_internal_call(&mut self, registration: &InternalCallRegistration) {
+        self.internal_calls.push(
+            &self.name,
+            registration.class_name,
+            registration.variable_name,
+            RegisteredInternalCall {
+                function: (registration.function)(),
+                signature: Some((registration.signature)()),
+                closure: None,
+                host: Some(InternalCallHostSlot::Static((registration.host)())),
+            },
         );
     }
 
+    /// Adds and uploads the calls in `registries` whose class is defined in this assembly
+    pub(crate) fn bind_internal_calls(&mut self, registries: &[&InternalCallRegistry]) {
+        let calls = self.registered_internal_calls(registries);
+        if calls.is_empty() {
+            return;
+        }
+
+        for ((class_name, variable_name), call) in calls {
+            self.internal_calls
+                .push(&self.name, class_name, variable_name, call.clone());
+        }
+
+        if let Err(errors) = self.upload_internal_calls() {
+            self.internal_call_errors = errors;
+        }
+    }
+
+    /// The calls in `registries` whose class is defined in this assembly, a call in a later registry replaces the one
+    /// with the same class and field in an earlier one
+    fn registered_internal_calls<'a>(
+        &self,
+        registries: &[&'a InternalCallRegistry],
+    ) -> BTreeMap<&'a (String, String), &'a RegisteredInternalCall> {
+        let class_names = self
+            .types
+            .iter()
+            .filter_map(|r#type| r#type.get_full_name().ok())
+            .collect::<HashSet<_>>();
+
+        let mut calls = BTreeMap::new();
+        for registry in registries {
+            for (key, call) in &registry.calls {
+                if class_names.contains(&key.0) {
+                    calls.insert(key, call);
+                }
+            }
+        }
+
+        calls
+    }
+
+    /// Binds every added internal call. The ones that couldn't be bound are logged and returned, the rest are still bound.
+    pub fn upload_internal_calls(&self) -> Result<(), Vec<InternalCallError>> {
+        self.internal_calls.upload(&self.host)
+    }
+
+    /// Makes every bound internal call of the assembly wait until the returned guard is dropped, so the native library
+    /// that implements them can be reloaded (see [`InternalCallPause::rebind`]). Calls that are already running
+    /// aren't waited for, so pause when none can be (e.g between frames). Pauses nest, the calls resume once every
+    /// guard is dropped.
+    pub fn pause_internal_calls(&self) -> InternalCallPause<'_> {
+        (self.host.managed_functions().pause_internal_calls)(self.assembly_id);
+
+        InternalCallPause { assembly: self }
+    }
+
+    /// Swaps the function pointers of the fields bound by `registry` (the calls whose class this assembly defines),
+    /// with calls paused while they're swapped. Use [`ManagedAssembly::pause_internal_calls`] to keep them paused
+    /// for the whole reload.
+    pub fn rebind_internal_calls(
+        &self,
+        registry: &InternalCallRegistry,
+    ) -> Result<(), Vec<InternalCallError>> {
+        self.pause_internal_calls().rebind(registry)
+    }
+
+    /// Every static `delegate*` field in the assembly that is still null, meaning calling it would crash.
+    /// Meant to be checked after the internal calls are uploaded, e.g to fail tests when a binding is missing.
+    pub fn unbound_internal_calls(&self) -> Vec<UnboundInternalCall> {
+        audit_internal_calls(&self.host, self.assembly_id, false)
+    }
+
+    /// Same as [`ManagedAssembly::unbound_internal_calls`], but also fills every unbound field with a stub that throws
+    /// `Coral.Managed.InternalCallNotBoundException` (naming the field), so calling it can be caught instead of crashing
+    pub fn stub_unbound_internal_calls(&self) -> Vec<UnboundInternalCall> {
+        audit_internal_calls(&self.host, self.assembly_id, true)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Anything but [`AssemblyLoadStatus::Success`] means the load failed, and the assembly has no name or types
+    pub fn load_status(&self) -> AssemblyLoadStatus {
+        self.load_status
+    }
+
+    /// Initializers that threw when the assembly was loaded, see [`AssemblyLoadContext::set_initializers`]
+    pub fn initializer_errors(&self) -> &[InitializerError] {
+        &self.initializer_errors
+    }
+
+    /// Internal calls from the host's or the context's registry that couldn't be bound when the assembly was loaded,
+    /// see [`AssemblyLoadContext::set_internal_calls`]
+    pub fn internal_call_errors(&self) -> &[InternalCallError] {
+        &self.internal_call_errors
+    }
+
+    pub fn version(&self) -> String {
+        let mut version = (self.host.managed_functions().get_assembly_version)(self.assembly_id);
+        let result = version.to_string();
+        CSharpNativeString::free(&mut version);
+
+        result
+    }
+
+    /// Names of the manifest resources embedded in the assembly
+    pub fn resource_names(&self) -> Vec<String> {
+        let get_assembly_resource_names =
+            &self.host.managed_functions().get_assembly_resource_names;
+
+        let mut name_count = 0i32;
+        get_assembly_resource_names(self.assembly_id, std::ptr::null_mut(), &mut name_count);
+
+        let mut names = Vec::<CSharpNativeString>::with_capacity(name_count as usize);
+        get_assembly_resource_names(self.assembly_id, names.as_mut_ptr(), &mut name_count);
+        unsafe {
+            names.set_len(name_count as usize);
+        }
+
+        let result = names.iter().map(CSharpNativeString::to_string).collect();
+        for name in &mut names {
+            CSharpNativeString::free(name);
+        }
+
+        result
+    }
+
+    /// The contents of the manifest resource `name`, `None` if the assembly doesn't have a resource with that name
+    pub fn read_resource(&self, name: &str) -> Option<Vec<u8>> {
+        let read_assembly_resource = &self.host.managed_functions().read_assembly_resource;
+        let name = ScopedCSharpNativeString::from_str(name);
+
+        let mut length = 0i64;
+        let found: bool = read_assembly_resource(
+            self.assembly_id,
+            name.inner(),
+            std::ptr::null_mut(),
+            &mut length,
+        )
+        .into();
+        if !found {
+            return None;
+        }
+
+        let mut data = vec![0u8; length as usize];
+        let read: bool = read_assembly_resource(
+            self.assembly_id,
+            name.inner(),
+            data.as_mut_ptr(),
+            &mut length,
+        )
+        .into();
+
+        read.then_some(data)
+    }
+
     pub fn get_type(&self, class_name: &str) -> Result<Arc<Type>, TypeCacheError> {
         self.host.type_cache().get_type_by_name(class_name)
     }
+
+    /// Every type defined in the assembly
+    pub fn types(&self) -> &[Arc<Type>] {
+        &self.types
+    }
+
+    /// Types declared directly in `namespace`, use `""` for the global namespace
+    pub fn types_in_namespace(&self, namespace: &str) -> Vec<Arc<Type>> {
+        self.indexed_types(self.type_index.in_namespace(namespace))
+    }
+
+    /// Types in this assembly that derive from `base_type`, directly or indirectly
+    pub fn subclasses_of(&self, base_type: &Type) -> Vec<Arc<Type>> {
+        self.indexed_types(self.type_index.subclasses_of(base_type.id))
+    }
+
+    /// Types in this assembly that implement the interface `interface_type`
+    pub fn implementors_of(&self, interface_type: &Type) -> Vec<Arc<Type>> {
+        self.indexed_types(self.type_index.implementors_of(interface_type.id))
+    }
+
+    /// Types in this assembly that have (or inherit) an attribute of type `attribute_type`
+    pub fn types_with_attribute(&self, attribute_type: &Type) -> Vec<Arc<Type>> {
+        self.indexed_types(self.type_index.with_attribute(attribute_type.id))
+    }
+
+    fn indexed_types(&self, indices: &[usize]) -> Vec<Arc<Type>> {
+        indices.iter().map(|i| self.types[*i].clone()).collect()
+    }
+}
+
+impl Drop for ManagedAssembly {
+    fn drop(&mut self) {
+        let rebound_internal_calls = self
+            .rebound_internal_calls
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let has_closures = self.internal_calls.has_closures()
+            || rebound_internal_calls
+                .iter()
+                .any(|rebound| rebound.table.has_closures());
+
+        // NOTE: The closures are freed with the assembly, so C# must not be able to pass their context back anymore
+        if has_closures {
+            (self.host.managed_functions().unbind_internal_closures)(self.assembly_id);
+        }
+    }
+}
+
+/// Returned by [`ManagedAssembly::pause_internal_calls`], resumes the internal calls of the assembly when dropped
+pub struct InternalCallPause<'a> {
+    assembly: &'a ManagedAssembly,
+}
+
+impl InternalCallPause<'_> {
+    /// Swaps the function pointers of the fields bound by `registry` (e.g with the functions of a reloaded library).
+    /// Paused calls continue with the new functions once the pause is dropped.
+    pub fn rebind(&self, registry: &InternalCallRegistry) -> Result<(), Vec<InternalCallError>> {
+        let assembly = self.assembly;
+
+        let mut table = InternalCallTable::default();
+        for ((class_name, variable_name), call) in assembly.registered_internal_calls(&[registry]) {
+            table.push(&assembly.name, class_name, variable_name, call.clone());
+        }
+
+        let result = table.upload(&assembly.host);
+
+        // NOTE: Calls that couldn't be bound keep their old function
+        let mut bound_calls = table.keys().iter().cloned().collect::<HashSet<_>>();
+        if let Err(errors) = &result {
+            for error in errors {
+                bound_calls.remove(&(error.class_name.clone(), error.variable_name.clone()));
+            }
+        }
+
+        let mut rebound_internal_calls = assembly
+            .rebound_internal_calls
+            .lock()
+            .expect("Rebound internal calls Mutex is poisoned");
+
+        // NOTE: Waiting calls look up their function and context once they resume, so once every call of an older table
+        // is bound to a newer one, only calls that were already running can still use it (which pausing doesn't cover either)
+        for rebound in rebound_internal_calls.iter_mut() {
+            rebound
+                .bound_calls
+                .retain(|call| !bound_calls.contains(call));
+        }
+        rebound_internal_calls.retain(|rebound| !rebound.bound_calls.is_empty());
+
+        if !bound_calls.is_empty() {
+            rebound_internal_calls.push(ReboundInternalCalls { table, bound_calls });
+        }
+
+        result
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// A table swapped in by [`InternalCallPause::rebind`], kept while any of its calls is still bound
+struct ReboundInternalCalls {
+    table: InternalCallTable,
+    bound_calls: HashSet<(String, String)>,
+}
+
+impl Drop for InternalCallPause<'_> {
+    fn drop(&mut self) {
+        (self.assembly.host.managed_functions().resume_internal_calls)(self.assembly.assembly_id);
+    }
+}
+
+/// Everything [`ManagedAssembly::load`] gathers, split out so it can be built on a worker thread
+/// (`ManagedAssembly` itself isn't `Send`)
+struct LoadedAssembly {
+    assembly_id: i32,
+    load_status: AssemblyLoadStatus,
+    name: String,
+    types: Vec<Arc<Type>>,
+    type_index: TypeIndex,
+}
+
+impl LoadedAssembly {
+    fn load(
+        host: &HostInstance,
+        assembly_id: i32,
+        context: Option<&ContextStamp>,
+    ) -> Result<Self, AssemblyLoadError> {
+        let managed_functions = host.managed_functions();
+
+        let load_status = (managed_functions.get_last_load_status)();
+        if let Some(err) = AssemblyLoadError::from_load_status(load_status, host) {
+            return Err(err);
+        }
+
+        // NOTE: Other failures still return the assembly, with its load status and without a name or types
+        if load_status != AssemblyLoadStatus::Success {
+            return Ok(Self {
+                assembly_id,
+                load_status,
+                name: String::new(),
+                types: vec![],
+                type_index: TypeIndex::default(),
+            });
+        }
+
+        let mut assembly_name = (managed_functions.get_assembly_name)(assembly_id);
+        let name = assembly_name.to_string();
+        CSharpNativeString::free(&mut assembly_name);
+
+        let type_index = TypeIndex::build(assembly_id, host);
+
+        let mut types = Vec::with_capacity(type_index.type_ids.len());
+        for type_id in &type_index.type_ids {
+            let mut r#type = Type::from_id(*type_id, host);
+            r#type.context = context.cloned();
+
+            let arc_type = Arc::new(r#type);
+            types.push(arc_type.clone());
+            host.type_cache().cache_type(arc_type);
+        }
+
+        Ok(Self {
+            assembly_id,
+            load_status,
+            name,
+            types,
+            type_index,
+        })
+    }
+
+    fn into_assembly(self, host: &HostInstance) -> Arc<ManagedAssembly> {
+        self.into_initialized_assembly(host, None, None)
+    }
+
+    /// Binds the host's and the context's internal calls before the initializers run, since they can call them
+    fn into_initialized_assembly(
+        self,
+        host: &HostInstance,
+        internal_calls: Option<&InternalCallRegistry>,
+        initializers: Option<&InitializerSettings>,
+    ) -> Arc<ManagedAssembly> {
+        let mut assembly = ManagedAssembly::new(
+            host.clone(),
+            self.assembly_id,
+            self.load_status,
+            self.name,
+            self.types,
+            self.type_index,
+        );
+
+        // NOTE: A failed load has no types to bind or initialize
+        if assembly.load_status == AssemblyLoadStatus::Success {
+            // NOTE: Scoped so the registry isn't locked while the initializers run
+            {
+                let host_internal_calls = host.internal_calls();
+
+                let mut registries = vec![&*host_internal_calls];
+                registries.extend(internal_calls);
+                assembly.bind_internal_calls(&registries);
+            }
+
+            if let Some(initializers) = initializers {
+                assembly.initializer_errors =
+                    run_initializers(host, assembly.assembly_id, initializers);
+            }
+        }
+
+        Arc::new(assembly)
+    }
+}
+
+/// An assembly being loaded on a worker thread, see [`AssemblyLoadContext::load_assembly_async`].
+/// The context isn't unloaded while the worker runs, dropping it waits for the load to finish.
+pub struct PendingAssemblyLoad {
+    context_id: i32,
+    worker: std::thread::JoinHandle<Result<LoadedAssembly, AssemblyLoadError>>,
+}
+
+/// The loads of a context that are running on worker threads
+#[derive(Default)]
+struct PendingLoads {
+    count: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl PendingLoads {
+    fn start(self: &Arc<Self>) -> PendingLoadGuard {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+
+        PendingLoadGuard(self.clone())
+    }
+
+    fn wait_for_all(&self) {
+        let count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        let _count = self
+            .finished
+            .wait_while(count, |count| *count > 0)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+/// Owned by the worker, so the load counts as finished even if the worker panics
+struct PendingLoadGuard(Arc<PendingLoads>);
+
+impl Drop for PendingLoadGuard {
+    fn drop(&mut self) {
+        *self.0.count.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+        self.0.finished.notify_all();
+    }
+}
+
+impl PendingAssemblyLoad {
+    /// Whether [`PendingAssemblyLoad::wait`] would return without blocking
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Blocks until the load is done and adds the assembly to `context`, which has to be the context that started it
+    /// ([`AssemblyLoadError::WrongContext`] otherwise). The initializers of the assembly run here, on the calling thread.
+    pub fn wait(
+        self,
+        context: &mut AssemblyLoadContext,
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        if self.context_id != context.context_id {
+            return Err(AssemblyLoadError::WrongContext);
+        }
+
+        let loaded = self
+            .worker
+            .join()
+            .expect("Assembly load worker thread panicked")?;
+
+        Ok(context.commit(loaded))
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum AssemblyLoadError {
     FileNotFound,
+    /// Rejected by the context's [`VerificationPolicy`], with the reason why
+    VerificationFailed(String),
+    /// References APIs the context's [`SandboxPolicy`] forbids
+    SandboxViolation(Vec<SandboxFinding>),
+    /// A [`PendingAssemblyLoad`] was waited on with a different context than the one that started it
+    WrongContext,
+}
+
+impl AssemblyLoadError {
+    /// The statuses that fail the load instead of being kept on the assembly
+    fn from_load_status(load_status: AssemblyLoadStatus, host: &HostInstance) -> Option<Self> {
+        match load_status {
+            AssemblyLoadStatus::VerificationFailed => {
+                let mut reason = (host.managed_functions().get_last_verification_failure)();
+                let result = reason.to_string();
+                CSharpNativeString::free(&mut reason);
+
+                Some(Self::VerificationFailed(result))
+            }
+            AssemblyLoadStatus::SandboxViolation => {
+                Some(Self::SandboxViolation(last_sandbox_findings(host)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Shared by an [`AssemblyLoadContext`] and every type and object that came from it, so using them after
+/// the context was unloaded can be detected instead of touching freed handles
+#[derive(Debug, Clone)]
+pub(crate) struct ContextStamp(Arc<AtomicBool>);
+
+impl ContextStamp {
+    fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    pub(crate) fn is_alive(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn invalidate(&self) {
+        self.0.store(false, Ordering::Release);
+    }
 }
 
 pub struct AssemblyLoadContext {
@@ -91,6 +696,14 @@ pub struct AssemblyLoadContext {
     host: HostInstance,
     // TODO: Make sure this is fine compared to StableVec + C++-style referencess
     loaded_assemblies: Vec<Arc<ManagedAssembly>>,
+    // NOTE: Boxed since C# holds on to a pointer to it
+    resolver: Box<ResolverState>,
+    // NOTE: Boxed for the same reason
+    verification: Box<VerificationState>,
+    initializers: Option<InitializerSettings>,
+    internal_calls: Option<InternalCallRegistry>,
+    stamp: ContextStamp,
+    pending_loads: Arc<PendingLoads>,
 }
 
 impl AssemblyLoadContext {
@@ -99,117 +712,287 @@ impl AssemblyLoadContext {
             context_id,
             host: host.clone(),
             loaded_assemblies: vec![],
+            resolver: Box::default(),
+            verification: Box::default(),
+            initializers: None,
+            internal_calls: None,
+            stamp: ContextStamp::new(),
+            pending_loads: Arc::default(),
         }
     }
 
-    pub fn load_assembly(
+    /// Dependencies that can't be found anywhere else are looked up as `<directory>/<name>.dll`.
+    /// Native libraries requested through `DllImport` are probed for in the same directories.
+    pub fn add_probing_directory(&mut self, directory: &std::path::Path) {
+        let directory = ScopedCSharpNativeString::from_str(&directory.to_string_lossy());
+
+        (self.host.managed_functions().add_probing_directory)(self.context_id, directory.inner());
+    }
+
+    /// Resolves managed and native dependencies using the `.deps.json` that sits next to
+    /// `component_assembly_path`, the same way `dotnet` does for an application.
+    pub fn use_dependency_manifest(&mut self, component_assembly_path: &std::path::Path) {
+        let path = ScopedCSharpNativeString::from_str(&component_assembly_path.to_string_lossy());
+
+        (self.host.managed_functions().set_dependency_manifest)(self.context_id, path.inner());
+    }
+
+    /// Called for every assembly that isn't in the dependency manifest, a probing directory or
+    /// already loaded. Returning `None` lets the load fail as it normally would.
+    pub fn set_assembly_resolver(
         &mut self,
-        path: &std::path::Path,
-    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
-        let mut path_cs_str = CSharpNativeString::new(path.to_str().unwrap());
+        resolver: impl Fn(&AssemblyName) -> Option<AssemblyResolution> + Send + Sync + 'static,
+    ) {
+        self.resolver.assembly_resolver = Some(Box::new(resolver));
+        self.upload_resolver();
+    }
 
-        let managed_functions = self.host.managed_functions();
+    /// Same as [`AssemblyLoadContext::set_assembly_resolver`] but for native libraries, the returned path
+    /// is passed to `LoadUnmanagedDllFromPath`.
+    pub fn set_unmanaged_resolver(
+        &mut self,
+        resolver: impl Fn(&str) -> Option<std::path::PathBuf> + Send + Sync + 'static,
+    ) {
+        self.resolver.unmanaged_resolver = Some(Box::new(resolver));
+        self.upload_resolver();
+    }
 
-        let assembly_id = (managed_functions.load_assembly)(self.context_id, path_cs_str.clone());
-        let load_status = (managed_functions.get_last_load_status)();
-        let mut name = String::new();
-        let mut types = Vec::new();
+    /// Every assembly loaded into this context from now on (dependencies included) has to pass `policy`,
+    /// otherwise loading it fails with [`AssemblyLoadError::VerificationFailed`] and nothing is loaded.
+    pub fn set_verification_policy(&mut self, policy: VerificationPolicy) {
+        let allowed_sha256 = policy
+            .allowed_sha256
+            .iter()
+            .map(|hash| ScopedCSharpNativeString::from_str(hash))
+            .collect::<Vec<_>>();
+        let required_public_key_tokens = policy
+            .required_public_key_tokens
+            .iter()
+            .map(|token| ScopedCSharpNativeString::from_str(token))
+            .collect::<Vec<_>>();
 
-        // TODO: Just return Err if != Success
-        if load_status == AssemblyLoadStatus::Success {
-            let mut assembly_name = (managed_functions.get_assembly_name)(assembly_id);
-            name = assembly_name.to_string();
-            CSharpNativeString::free(&mut assembly_name);
+        let native_allowed_sha256 = allowed_sha256
+            .iter()
+            .map(|hash| hash.inner())
+            .collect::<Vec<_>>();
+        let native_required_public_key_tokens = required_public_key_tokens
+            .iter()
+            .map(|token| token.inner())
+            .collect::<Vec<_>>();
 
-            let mut type_count = -1;
-            (managed_functions.get_assembly_types)(
-                assembly_id,
-                std::ptr::null_mut(),
-                &mut type_count,
-            );
+        let verifier = policy
+            .verifier
+            .is_some()
+            .then_some(verify_assembly_callback as _);
+        self.verification.verifier = policy.verifier;
 
-            let mut type_ids = Vec::<TypeId>::with_capacity(type_count as usize);
-            (managed_functions.get_assembly_types)(
-                assembly_id,
-                type_ids.as_mut_ptr(),
-                &mut type_count,
-            );
-            unsafe {
-                type_ids.set_len(type_count as usize);
-            }
+        (self.host.managed_functions().set_verification_policy)(
+            self.context_id,
+            native_allowed_sha256.as_ptr(),
+            native_allowed_sha256.len() as i32,
+            native_required_public_key_tokens.as_ptr(),
+            native_required_public_key_tokens.len() as i32,
+            verifier,
+            self.verification.as_ref() as *const VerificationState as *mut _,
+        );
+    }
 
-            for type_id in type_ids {
-                let arc_type = Arc::new(Type::from_id(type_id, &self.host));
-                types.push(arc_type.clone());
-                self.host.type_cache().cache_type(arc_type);
-            }
+    /// Every assembly loaded into this context from now on (dependencies included) is scanned for references
+    /// to APIs `policy` forbids, loading it fails with [`AssemblyLoadError::SandboxViolation`] if there are any.
+    pub fn set_sandbox_policy(&mut self, policy: &SandboxPolicy) {
+        let denied = policy
+            .denied
+            .iter()
+            .map(|entry| ScopedCSharpNativeString::from_str(entry))
+            .collect::<Vec<_>>();
+        let allowed = policy
+            .allowed
+            .iter()
+            .map(|entry| ScopedCSharpNativeString::from_str(entry))
+            .collect::<Vec<_>>();
+
+        let native_denied = denied.iter().map(|entry| entry.inner()).collect::<Vec<_>>();
+        let native_allowed = allowed
+            .iter()
+            .map(|entry| entry.inner())
+            .collect::<Vec<_>>();
+
+        (self.host.managed_functions().set_sandbox_policy)(
+            self.context_id,
+            native_denied.as_ptr(),
+            native_denied.len() as i32,
+            native_allowed.as_ptr(),
+            native_allowed.len() as i32,
+            policy.deny_pinvoke.into(),
+        );
+    }
+
+    /// After every successful load into this context, the static methods of the new assembly marked with the attribute
+    /// `attribute` (fully qualified, e.g `MyEngine.SharpenInitAttribute`) are called with `argument`, ordered by the
+    /// attribute's `Priority` (lowest first). Exceptions they throw end up in [`ManagedAssembly::initializer_errors`].
+    pub fn set_initializers(&mut self, attribute: &str, argument: InitializerArgument) {
+        self.initializers = Some(InitializerSettings::new(attribute, argument));
+    }
+
+    /// Binds the calls in `registry` to every assembly loaded into this context from now on that defines their class,
+    /// before its initializers run. They're bound together with [`HostInstance::internal_calls`], replacing the host's
+    /// call for the same field. Calls that can't be bound end up in [`ManagedAssembly::internal_call_errors`].
+    pub fn set_internal_calls(&mut self, registry: InternalCallRegistry) {
+        self.internal_calls = Some(registry);
+    }
+
+    /// Makes the assemblies (and PDBs) in `source` available to this context. Dependencies that aren't found
+    /// anywhere else are looked up by file name in every mounted source, before the assembly resolver is asked.
+    pub fn mount(&mut self, source: impl VirtualAssemblySource + 'static) {
+        self.resolver.mounts.push(Arc::new(source));
+        self.upload_resolver();
+    }
+
+    /// Loads the assembly at `entry` (e.g `Scripts/Game.dll`) from the first mounted source that has it,
+    /// together with the PDB next to it if there is one.
+    pub fn load_mounted_assembly(
+        &mut self,
+        entry: &str,
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        let (assembly, symbols) = self
+            .resolver
+            .mounts
+            .iter()
+            .find_map(|source| read_assembly(source.as_ref(), entry))
+            .ok_or(AssemblyLoadError::FileNotFound)?;
+
+        match symbols {
+            Some(symbols) => self.load_assembly_from_memory_with_symbols(&assembly, &symbols),
+            None => self.load_assembly_from_memory(&assembly),
         }
+    }
+
+    pub fn load_assembly(
+        &mut self,
+        path: &std::path::Path,
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        let mut path_cs_str = CSharpNativeString::new(path.to_str().unwrap());
+
+        let assembly_id =
+            (self.host.managed_functions().load_assembly)(self.context_id, path_cs_str.clone());
 
         CSharpNativeString::free(&mut path_cs_str);
 
-        let assembly = Arc::new(ManagedAssembly::new(
-            self.host.clone(),
-            assembly_id,
-            load_status,
-            name,
-            types,
-        ));
-        self.loaded_assemblies.push(assembly.clone());
+        self.finish_load(assembly_id)
+    }
+
+    /// Same as [`AssemblyLoadContext::load_assembly`], but the load and the construction of the assembly's types
+    /// happen on a worker thread. The assembly is only added to [`AssemblyLoadContext::loaded_assemblies`] once
+    /// [`PendingAssemblyLoad::wait`] is called.
+    // NOTE: The resolvers and policies of the context can be called from the worker, so they shouldn't be replaced while a load is pending
+    pub fn load_assembly_async(&self, path: &std::path::Path) -> PendingAssemblyLoad {
+        let host = self.host.clone();
+        let context_id = self.context_id;
+        let stamp = self.stamp.clone();
+        let path = path.to_path_buf();
+        let pending = self.pending_loads.start();
+
+        let worker = std::thread::spawn(move || {
+            let _pending = pending;
+            let path = ScopedCSharpNativeString::from_str(&path.to_string_lossy());
 
-        Ok(assembly)
+            let assembly_id = (host.managed_functions().load_assembly)(context_id, path.inner());
+            LoadedAssembly::load(&host, assembly_id, Some(&stamp))
+        });
+
+        PendingAssemblyLoad { context_id, worker }
     }
 
     pub fn load_assembly_from_memory(
         &mut self,
         bytes: &[u8],
     ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
-        let managed_functions = self.host.managed_functions();
+        let assembly_id = (self.host.managed_functions().load_assembly_from_memory)(
+            self.context_id,
+            bytes.as_ptr(),
+            bytes.len() as i64,
+        );
+
+        self.finish_load(assembly_id)
+    }
 
-        let assembly_id = (managed_functions.load_assembly_from_memory)(
+    /// Same as [`AssemblyLoadContext::load_assembly_from_memory`], but also loads the portable PDB
+    /// in `symbols` so exceptions thrown from the assembly keep their file and line information.
+    pub fn load_assembly_from_memory_with_symbols(
+        &mut self,
+        bytes: &[u8],
+        symbols: &[u8],
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        let assembly_id = (self
+            .host
+            .managed_functions()
+            .load_assembly_from_memory_with_symbols)(
             self.context_id,
             bytes.as_ptr(),
             bytes.len() as i64,
+            symbols.as_ptr(),
+            symbols.len() as i64,
         );
-        let load_status = (managed_functions.get_last_load_status)();
-        let mut name = String::new();
-        let mut types = Vec::new();
 
-        if load_status == AssemblyLoadStatus::Success {
-            let mut assembly_name = (managed_functions.get_assembly_name)(assembly_id);
-            name = assembly_name.to_string();
-            CSharpNativeString::free(&mut assembly_name);
+        self.finish_load(assembly_id)
+    }
 
-            let mut type_count = 0;
-            (managed_functions.get_assembly_types)(
-                assembly_id,
-                std::ptr::null_mut(),
-                &mut type_count,
-            );
+    /// Compiles `sources` into an assembly called `assembly_name` (with a portable PDB) using the Roslyn compiler
+    /// of the installed .NET SDK, and loads it into this context. The framework assemblies are always referenced,
+    /// `references` are paths to any additional assemblies the sources need.
+    pub fn compile_and_load(
+        &mut self,
+        assembly_name: &str,
+        sources: &[CSharpSource],
+        references: &[&std::path::Path],
+    ) -> Result<Arc<ManagedAssembly>, CompileError> {
+        let assembly_name = ScopedCSharpNativeString::from_str(assembly_name);
+        let source_paths = sources
+            .iter()
+            .map(|source| ScopedCSharpNativeString::from_str(source.path))
+            .collect::<Vec<_>>();
+        let source_code = sources
+            .iter()
+            .map(|source| ScopedCSharpNativeString::from_str(source.code))
+            .collect::<Vec<_>>();
+        let references = references
+            .iter()
+            .map(|reference| ScopedCSharpNativeString::from_str(&reference.to_string_lossy()))
+            .collect::<Vec<_>>();
 
-            let mut type_ids = Vec::<TypeId>::with_capacity(type_count as usize);
-            (managed_functions.get_assembly_types)(
-                assembly_id,
-                type_ids.as_mut_ptr(),
-                &mut type_count,
-            );
-
-            for type_id in type_ids {
-                let arc_type = Arc::new(Type::from_id(type_id, &self.host));
-                types.push(arc_type.clone());
-                self.host.type_cache().cache_type(arc_type);
-            }
-        }
+        let native_source_paths = source_paths
+            .iter()
+            .map(|path| path.inner())
+            .collect::<Vec<_>>();
+        let native_source_code = source_code
+            .iter()
+            .map(|code| code.inner())
+            .collect::<Vec<_>>();
+        let native_references = references
+            .iter()
+            .map(|reference| reference.inner())
+            .collect::<Vec<_>>();
 
-        let assembly = Arc::new(ManagedAssembly::new(
-            self.host.clone(),
-            assembly_id,
-            load_status,
-            name,
-            types,
-        ));
-        self.loaded_assemblies.push(assembly.clone());
+        let managed_functions = self.host.managed_functions();
+        let assembly_id = (managed_functions.compile_and_load_assembly)(
+            self.context_id,
+            assembly_name.inner(),
+            native_source_paths.as_ptr(),
+            native_source_code.as_ptr(),
+            sources.len() as i32,
+            native_references.as_ptr(),
+            native_references.len() as i32,
+        );
 
-        Ok(assembly)
+        match (managed_functions.get_last_load_status)() {
+            AssemblyLoadStatus::CompilerNotFound => Err(CompileError::CompilerNotFound),
+            AssemblyLoadStatus::CompilationFailed => Err(CompileError::CompilationFailed(
+                last_compiler_diagnostics(&self.host),
+            )),
+            _ => self
+                .finish_load(assembly_id)
+                .map_err(CompileError::AssemblyLoadError),
+        }
     }
 
     pub fn context_id(&self) -> i32 {
@@ -219,10 +1002,40 @@ impl AssemblyLoadContext {
     pub fn loaded_assemblies(&self) -> &Vec<Arc<ManagedAssembly>> {
         &self.loaded_assemblies
     }
+
+    fn upload_resolver(&self) {
+        (self.host.managed_functions().set_assembly_resolver)(
+            self.context_id,
+            resolve_assembly_callback,
+            resolve_unmanaged_callback,
+            self.resolver.as_ref() as *const ResolverState as *mut _,
+        );
+    }
+
+    fn finish_load(&mut self, assembly_id: i32) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        let loaded = LoadedAssembly::load(&self.host, assembly_id, Some(&self.stamp))?;
+
+        Ok(self.commit(loaded))
+    }
+
+    fn commit(&mut self, loaded: LoadedAssembly) -> Arc<ManagedAssembly> {
+        let assembly = loaded.into_initialized_assembly(
+            &self.host,
+            self.internal_calls.as_ref(),
+            self.initializers.as_ref(),
+        );
+        self.loaded_assemblies.push(assembly.clone());
+
+        assembly
+    }
 }
 
 impl Drop for AssemblyLoadContext {
     fn drop(&mut self) {
+        // NOTE: A worker still loading into the context uses its resolvers, and the context itself
+        self.pending_loads.wait_for_all();
+
+        self.stamp.invalidate();
         self.host.unload_assembly_load_context(self);
     }
 }
@@ -0,0 +1,266 @@
+use std::{
+    any::Any, ffi::c_void, fmt::Display, future::Future, panic::AssertUnwindSafe, pin::Pin,
+    sync::Arc,
+};
+
+use crate::{
+    host_instance::{HostInstance, log_message, panic_message},
+    internal_call::{InternalCallHost, InternalCallReturn},
+    managed_object::ManagedObject,
+    managed_type::{FfiSafe, ManagedType},
+    message_level::MessageLevel,
+    string::{CSharpNativeString, ScopedCSharpNativeString},
+};
+
+/// Runs the futures of async internal calls, see [`crate::host_instance::HostInstance::set_internal_call_executor`].
+/// Implemented for closures, e.g `move |future| { runtime.spawn(future); }`.
+pub trait InternalCallExecutor: Send + Sync + 'static {
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>);
+}
+
+impl<F> InternalCallExecutor for F
+where
+    F: Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static,
+{
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
+        self(future)
+    }
+}
+
+/// A value an async internal call completes its task with, anything an internal call can return
+pub trait InternalCallTaskValue {
+    type Native: FfiSafe;
+
+    fn into_native(self) -> Self::Native;
+}
+
+impl<T: InternalCallReturn> InternalCallTaskValue for T {
+    type Native = T::Native;
+
+    fn into_native(self) -> Self::Native {
+        InternalCallReturn::into_native(self)
+    }
+}
+
+/// What the future of an async internal call resolves to, which completes its C# `Task<T>`.
+/// `Err` fails the task with an `InternalCallFailedException` instead.
+pub trait InternalCallTaskOutput {
+    type Value: InternalCallTaskValue;
+
+    fn into_result(self) -> Result<Self::Value, String>;
+}
+
+macro_rules! impl_internal_call_task_output {
+    ($($type:ty),*) => {
+        $(
+            impl InternalCallTaskOutput for $type {
+                type Value = $type;
+
+                fn into_result(self) -> Result<Self::Value, String> {
+                    Ok(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_internal_call_task_output!(
+    (),
+    i8,
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+    i64,
+    u64,
+    f32,
+    f64,
+    bool,
+    String,
+    ManagedObject
+);
+
+impl<T: 'static> InternalCallTaskOutput for *mut T {
+    type Value = *mut T;
+
+    fn into_result(self) -> Result<Self::Value, String> {
+        Ok(self)
+    }
+}
+
+impl<T: InternalCallTaskValue, E: Display> InternalCallTaskOutput for Result<T, E> {
+    type Value = T;
+
+    fn into_result(self) -> Result<Self::Value, String> {
+        self.map_err(|err| err.to_string())
+    }
+}
+
+/// A closure returning a future, that can be bound with [`crate::assembly::ManagedAssembly::add_async_internal_call`].
+/// Implemented for closures with up to 8 parameters.
+pub trait AsyncInternalClosure<Args>: Send + Sync + 'static {
+    /// Includes the context pointer C# passes back as the first argument, and the task handle as the last
+    fn parameter_types() -> Vec<ManagedType>;
+    /// An `extern "system" fn` that calls the closure its context (an `AsyncInternalCallContext`) points to, and spawns
+    /// the future it returns
+    fn trampoline() -> *const c_void;
+}
+
+/// What the context of an async internal call points to, the closure and the host that bound it
+pub(crate) struct AsyncInternalCallContext<F> {
+    closure: F,
+    pub(crate) host: Arc<InternalCallHost>,
+}
+
+impl<F> AsyncInternalCallContext<F> {
+    pub(crate) fn new(closure: F) -> Self {
+        Self {
+            closure,
+            host: Arc::new(InternalCallHost::new()),
+        }
+    }
+}
+
+macro_rules! impl_async_internal_closure {
+    ($($arg:ident),*) => {
+        impl<Func, Fut, $($arg: FfiSafe),*> AsyncInternalClosure<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output: InternalCallTaskOutput> + Send + 'static,
+        {
+            fn parameter_types() -> Vec<ManagedType> {
+                vec![
+                    ManagedType::Pointer,
+                    $($arg::MANAGED_TYPE,)*
+                    ManagedType::Pointer,
+                ]
+            }
+
+            fn trampoline() -> *const c_void {
+                #[allow(non_snake_case)]
+                extern "system" fn trampoline<Func, Fut, $($arg),*>(
+                    context: *const c_void,
+                    $($arg: $arg,)*
+                    task: *mut c_void,
+                ) where
+                    Func: Fn($($arg),*) -> Fut,
+                    Fut: Future<Output: InternalCallTaskOutput> + Send + 'static,
+                {
+                    let context = unsafe { &*(context as *const AsyncInternalCallContext<Func>) };
+                    let Some(host) = context.host.get() else {
+                        log_message(
+                            "Async internal call made before a HostInstance bound it, its task never completes"
+                                .to_string(),
+                            MessageLevel::Error,
+                        );
+                        return;
+                    };
+
+                    let task = InternalCallTask { handle: task, host };
+                    // NOTE: A panic can't unwind into C#, so it fails the task instead
+                    let future = match std::panic::catch_unwind(AssertUnwindSafe(|| (context.closure)($($arg),*))) {
+                        Ok(future) => future,
+                        Err(payload) => {
+                            task.fail(&format!(
+                                "The async internal call panicked: {}",
+                                panic_message(payload.as_ref())
+                            ));
+                            return;
+                        }
+                    };
+                    spawn_internal_call_task(task, future);
+                }
+
+                trampoline::<Func, Fut, $($arg),*> as *const c_void
+            }
+        }
+    };
+}
+
+impl_async_internal_closure!();
+impl_async_internal_closure!(A);
+impl_async_internal_closure!(A, B);
+impl_async_internal_closure!(A, B, C);
+impl_async_internal_closure!(A, B, C, D);
+impl_async_internal_closure!(A, B, C, D, E);
+impl_async_internal_closure!(A, B, C, D, E, F);
+impl_async_internal_closure!(A, B, C, D, E, F, G);
+impl_async_internal_closure!(A, B, C, D, E, F, G, H);
+
+/// The `GCHandle` of the C# task an async internal call completes, the task is failed if it's dropped before that
+/// (e.g if the executor drops the future)
+struct InternalCallTask {
+    handle: *mut c_void,
+    host: HostInstance,
+}
+
+// NOTE: C# can complete the task from any thread
+unsafe impl Send for InternalCallTask {}
+
+impl InternalCallTask {
+    fn complete<T: InternalCallTaskValue>(mut self, result: Result<T, String>) {
+        let handle = std::mem::replace(&mut self.handle, std::ptr::null_mut());
+        let host = &self.host;
+
+        let value = match result {
+            Ok(value) => value,
+            Err(message) => {
+                let message = ScopedCSharpNativeString::from_str(&message);
+                (host.managed_functions().fail_internal_call_task)(handle, message.inner());
+                return;
+            }
+        };
+
+        let mut native = value.into_native();
+        let native_ptr = if size_of::<T::Native>() == 0 {
+            std::ptr::null()
+        } else {
+            &native as *const T::Native as *const c_void
+        };
+
+        (host.managed_functions().complete_internal_call_task)(
+            handle,
+            native_ptr,
+            <T::Native as FfiSafe>::MANAGED_TYPE,
+        );
+
+        // NOTE: C# copies the string before the task is completed, so unlike a returned string it's freed again
+        if let Some(string) = (&mut native as &mut dyn Any).downcast_mut::<CSharpNativeString>() {
+            CSharpNativeString::free_co_task_mem(string);
+        }
+    }
+
+    fn fail(self, message: &str) {
+        self.complete(Err::<(), _>(message.to_string()));
+    }
+}
+
+impl Drop for InternalCallTask {
+    fn drop(&mut self) {
+        if self.handle.is_null() {
+            return;
+        }
+
+        let task = InternalCallTask {
+            handle: std::mem::replace(&mut self.handle, std::ptr::null_mut()),
+            host: self.host.clone(),
+        };
+        task.fail("The async internal call was dropped before it completed.");
+    }
+}
+
+fn spawn_internal_call_task<Fut>(task: InternalCallTask, future: Fut)
+where
+    Fut: Future<Output: InternalCallTaskOutput> + Send + 'static,
+{
+    let Some(executor) = task.host.internal_call_executor() else {
+        task.fail("No executor was set for async internal calls, see HostInstance::set_internal_call_executor.");
+        return;
+    };
+
+    executor.spawn(Box::pin(async move {
+        let result = future.await.into_result();
+        task.complete(result);
+    }));
+}
@@ -0,0 +1,81 @@
+use crate::{assembly::AssemblyLoadError, host_instance::HostInstance, string::CSharpNativeString};
+
+/// A single C# source file to compile, `path` only shows up in diagnostics and stack traces
+#[derive(Debug, Clone, Copy)]
+pub struct CSharpSource<'a> {
+    pub path: &'a str,
+    pub code: &'a str,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticSeverity {
+    Hidden,
+    Info,
+    Warning,
+    Error,
+}
+
+/// A diagnostic reported by the C# compiler, `line` and `column` are 1-based
+#[derive(Debug, Clone)]
+pub struct CompilerDiagnostic {
+    pub file: String,
+    pub line: i32,
+    pub column: i32,
+    pub severity: DiagnosticSeverity,
+    /// The compiler's diagnostic id (e.g CS0103)
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum CompileError {
+    /// No .NET SDK with a Roslyn compiler could be found next to the runtime (or in `DOTNET_ROOT`)
+    CompilerNotFound,
+    /// Every diagnostic of the failed compilation, including warnings
+    CompilationFailed(Vec<CompilerDiagnostic>),
+    AssemblyLoadError(AssemblyLoadError),
+}
+
+#[repr(C)]
+pub(crate) struct NativeCompilerDiagnostic {
+    file: CSharpNativeString,
+    line: i32,
+    column: i32,
+    severity: DiagnosticSeverity,
+    code: CSharpNativeString,
+    message: CSharpNativeString,
+}
+
+pub(crate) fn last_compiler_diagnostics(host: &HostInstance) -> Vec<CompilerDiagnostic> {
+    let get_last_compiler_diagnostics = &host.managed_functions().get_last_compiler_diagnostics;
+
+    let mut diagnostic_count = 0i32;
+    get_last_compiler_diagnostics(std::ptr::null_mut(), &mut diagnostic_count);
+
+    let mut diagnostics = Vec::<NativeCompilerDiagnostic>::with_capacity(diagnostic_count as usize);
+    get_last_compiler_diagnostics(diagnostics.as_mut_ptr(), &mut diagnostic_count);
+    unsafe {
+        diagnostics.set_len(diagnostic_count as usize);
+    }
+
+    let result = diagnostics
+        .iter()
+        .map(|diagnostic| CompilerDiagnostic {
+            file: diagnostic.file.to_string(),
+            line: diagnostic.line,
+            column: diagnostic.column,
+            severity: diagnostic.severity,
+            code: diagnostic.code.to_string(),
+            message: diagnostic.message.to_string(),
+        })
+        .collect();
+
+    for diagnostic in &mut diagnostics {
+        CSharpNativeString::free(&mut diagnostic.file);
+        CSharpNativeString::free(&mut diagnostic.code);
+        CSharpNativeString::free(&mut diagnostic.message);
+    }
+
+    result
+}
@@ -0,0 +1,102 @@
+use crate::{Bool32, host_instance::HostInstance, string::CSharpNativeString};
+
+/// A snapshot of a managed `AssemblyLoadContext`, see [`HostInstance::contexts`]
+#[derive(Debug, Clone)]
+pub struct AssemblyLoadContextInfo {
+    pub id: i32,
+    pub name: String,
+    /// Names of the assemblies loaded into the context
+    pub assemblies: Vec<String>,
+    /// Number of types defined by the loaded assemblies
+    pub type_count: usize,
+    /// `GCHandle`s to objects from this context that haven't been destroyed yet
+    pub outstanding_handles: usize,
+    /// The context was unloaded, but the GC hasn't collected it yet (usually because something still references it)
+    pub unload_pending: bool,
+}
+
+#[repr(C)]
+pub(crate) struct NativeAssemblyLoadContextInfo {
+    context_id: i32,
+    name: CSharpNativeString,
+    assemblies_offset: i32,
+    assembly_count: i32,
+    type_count: i32,
+    outstanding_handle_count: i32,
+    unload_pending: Bool32,
+}
+
+pub(crate) fn snapshot_contexts(host: &HostInstance) -> Vec<AssemblyLoadContextInfo> {
+    let get_assembly_load_contexts = &host.managed_functions().get_assembly_load_contexts;
+
+    let mut contexts = Vec::<NativeAssemblyLoadContextInfo>::new();
+    let mut assembly_names = Vec::<CSharpNativeString>::new();
+
+    // NOTE: Other threads can add contexts and assemblies between two calls, so this retries until everything fit
+    loop {
+        let mut context_count = 0i32;
+        let mut assembly_name_count = 0i32;
+        get_assembly_load_contexts(
+            contexts.as_mut_ptr(),
+            contexts.capacity() as i32,
+            &mut context_count,
+            assembly_names.as_mut_ptr(),
+            assembly_names.capacity() as i32,
+            &mut assembly_name_count,
+        );
+        unsafe {
+            contexts.set_len((context_count as usize).min(contexts.capacity()));
+            assembly_names.set_len((assembly_name_count as usize).min(assembly_names.capacity()));
+        }
+
+        if contexts.len() == context_count as usize
+            && assembly_names.len() == assembly_name_count as usize
+        {
+            break;
+        }
+
+        free_snapshot(&mut contexts, &mut assembly_names);
+        contexts.reserve_exact(context_count as usize);
+        assembly_names.reserve_exact(assembly_name_count as usize);
+    }
+
+    let infos = contexts
+        .iter()
+        .map(|context| {
+            let assemblies_start = context.assemblies_offset as usize;
+            let assemblies_end = assemblies_start + context.assembly_count as usize;
+
+            AssemblyLoadContextInfo {
+                id: context.context_id,
+                name: context.name.to_string(),
+                assemblies: assembly_names[assemblies_start..assemblies_end]
+                    .iter()
+                    .map(CSharpNativeString::to_string)
+                    .collect(),
+                type_count: context.type_count as usize,
+                outstanding_handles: context.outstanding_handle_count as usize,
+                unload_pending: context.unload_pending.into(),
+            }
+        })
+        .collect();
+
+    free_snapshot(&mut contexts, &mut assembly_names);
+
+    infos
+}
+
+/// Frees the strings C# allocated and empties both vectors
+fn free_snapshot(
+    contexts: &mut Vec<NativeAssemblyLoadContextInfo>,
+    assembly_names: &mut Vec<CSharpNativeString>,
+) {
+    for context in contexts.iter_mut() {
+        CSharpNativeString::free(&mut context.name);
+    }
+    for assembly_name in assembly_names.iter_mut() {
+        CSharpNativeString::free(assembly_name);
+    }
+
+    contexts.clear();
+    assembly_names.clear();
+}
@@ -3,10 +3,21 @@ use std::ffi::c_void;
 use netcorehost::hostfxr::ManagedFunction;
 
 use crate::{
-    Bool32, ManagedHandle, TypeAccessibility, TypeId, managed_type::ManagedType,
+    Bool32, ManagedHandle, TypeAccessibility, TypeId,
+    compiler::NativeCompilerDiagnostic,
+    context_info::NativeAssemblyLoadContextInfo,
+    initializer::NativeInitializerError,
+    internal_call::{NativeInternalCallError, NativeUnboundInternalCall},
+    managed_type::ManagedType,
+    resolver::{AssemblyResolverCallbackFn, UnmanagedResolverCallbackFn},
+    sandbox::NativeSandboxFinding,
     string::CSharpNativeString,
+    type_index::TypeIndexEntry,
+    verification::AssemblyVerifierCallbackFn,
 };
 
+// NOTE: Only ever constructed by C#
+#[allow(dead_code)]
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AssemblyLoadStatus {
@@ -16,17 +27,76 @@ pub enum AssemblyLoadStatus {
     InvalidFilePath,
     InvalidAssembly,
     UnknownError,
+    CompilerNotFound,
+    CompilationFailed,
+    VerificationFailed,
+    SandboxViolation,
 }
 
-pub type SetInternalCallsFn = extern "system" fn(*mut std::ffi::c_void, i32); // TODO: figure out what *mut c_void is supposed to be
+pub type SetInternalCallsFn = extern "system" fn(*mut std::ffi::c_void, i32) -> i32; // TODO: figure out what *mut c_void is supposed to be
 pub type CreateAssemblyLoadContextFn = extern "system" fn(CSharpNativeString) -> i32;
+pub type GetSharedAssemblyLoadContextIdFn = extern "system" fn() -> i32;
 pub type UnloadAssemblyLoadContextFn = extern "system" fn(i32);
 pub type LoadAssemblyFn = extern "system" fn(i32, CSharpNativeString) -> i32;
 pub type LoadAssemblyFromMemoryFn = extern "system" fn(i32, *const u8, i64) -> i32;
+pub type LoadAssemblyFromMemoryWithSymbolsFn =
+    extern "system" fn(i32, *const u8, i64, *const u8, i64) -> i32;
+pub type AddProbingDirectoryFn = extern "system" fn(i32, CSharpNativeString);
+pub type SetDependencyManifestFn = extern "system" fn(i32, CSharpNativeString);
+pub type SetAssemblyResolverFn =
+    extern "system" fn(i32, AssemblyResolverCallbackFn, UnmanagedResolverCallbackFn, *mut c_void);
+pub type CompileAndLoadAssemblyFn = extern "system" fn(
+    i32,
+    CSharpNativeString,
+    *const CSharpNativeString,
+    *const CSharpNativeString,
+    i32,
+    *const CSharpNativeString,
+    i32,
+) -> i32;
+pub type GetLastCompilerDiagnosticsFn = extern "system" fn(*mut NativeCompilerDiagnostic, *mut i32);
+pub type GetAssemblyLoadContextsFn = extern "system" fn(
+    *mut NativeAssemblyLoadContextInfo,
+    i32,
+    *mut i32,
+    *mut CSharpNativeString,
+    i32,
+    *mut i32,
+);
+pub type SetVerificationPolicyFn = extern "system" fn(
+    i32,
+    *const CSharpNativeString,
+    i32,
+    *const CSharpNativeString,
+    i32,
+    Option<AssemblyVerifierCallbackFn>,
+    *mut c_void,
+);
+pub type GetLastVerificationFailureFn = extern "system" fn() -> CSharpNativeString;
+pub type SetSandboxPolicyFn =
+    extern "system" fn(i32, *const CSharpNativeString, i32, *const CSharpNativeString, i32, Bool32);
+pub type GetLastSandboxFindingsFn = extern "system" fn(*mut NativeSandboxFinding, *mut i32);
 pub type GetLastLoadStatusFn = extern "system" fn() -> AssemblyLoadStatus;
 pub type GetAssemblyNameFn = extern "system" fn(i32) -> CSharpNativeString;
+pub type GetAssemblyVersionFn = extern "system" fn(i32) -> CSharpNativeString;
+pub type GetAssemblyResourceNamesFn = extern "system" fn(i32, *mut CSharpNativeString, *mut i32);
+pub type ReadAssemblyResourceFn =
+    extern "system" fn(i32, CSharpNativeString, *mut u8, *mut i64) -> Bool32;
+pub type RunAssemblyInitializersFn =
+    extern "system" fn(i32, CSharpNativeString, *mut c_void, Bool32) -> i32;
+pub type GetLastInitializerErrorsFn = extern "system" fn(*mut NativeInitializerError, *mut i32);
+pub type GetLastInternalCallErrorsFn = extern "system" fn(*mut NativeInternalCallError, *mut i32);
+pub type AuditInternalCallsFn = extern "system" fn(i32, Bool32) -> i32;
+pub type UnbindInternalClosuresFn = extern "system" fn(i32);
+pub type PauseInternalCallsFn = extern "system" fn(i32);
+pub type ResumeInternalCallsFn = extern "system" fn(i32);
+pub type GetLastUnboundInternalCallsFn =
+    extern "system" fn(*mut NativeUnboundInternalCall, *mut i32);
+pub type CompleteInternalCallTaskFn = extern "system" fn(*mut c_void, *const c_void, ManagedType);
+pub type FailInternalCallTaskFn = extern "system" fn(*mut c_void, CSharpNativeString);
 
-pub type GetAssemblyTypesFn = extern "system" fn(i32, *mut TypeId, *mut i32);
+pub type GetAssemblyTypeIndexFn =
+    extern "system" fn(i32, *mut TypeIndexEntry, i32, *mut i32, *mut TypeId, i32, *mut i32);
 pub type GetTypeIdFn = extern "system" fn(CSharpNativeString, *mut TypeId);
 pub type GetFullTypeNameFn = extern "system" fn(TypeId) -> CSharpNativeString;
 pub type GetAssemblyQualifiedNameFn = extern "system" fn(TypeId) -> CSharpNativeString;
@@ -62,6 +132,7 @@ pub type GetPropertyInfoAttributesFn = extern "system" fn(ManagedHandle, *mut Ty
 pub type GetAttributeFieldValueFn =
     extern "system" fn(ManagedHandle, CSharpNativeString, *mut c_void);
 pub type GetAttributeTypeFn = extern "system" fn(ManagedHandle, *mut TypeId);
+pub type DestroyAttributeFn = extern "system" fn(ManagedHandle);
 
 pub type CreateObjectFn =
     extern "system" fn(TypeId, Bool32, *const *mut c_void, *const ManagedType, i32) -> *mut c_void;
@@ -103,12 +174,38 @@ pub type WaitForPendingFinalizersFn = extern "system" fn(); */
 
 pub struct CoralManagedFunctions {
     pub set_internal_calls: ManagedFunction<SetInternalCallsFn>,
+    pub get_last_internal_call_errors: ManagedFunction<GetLastInternalCallErrorsFn>,
+    pub audit_internal_calls: ManagedFunction<AuditInternalCallsFn>,
+    pub unbind_internal_closures: ManagedFunction<UnbindInternalClosuresFn>,
+    pub pause_internal_calls: ManagedFunction<PauseInternalCallsFn>,
+    pub resume_internal_calls: ManagedFunction<ResumeInternalCallsFn>,
+    pub get_last_unbound_internal_calls: ManagedFunction<GetLastUnboundInternalCallsFn>,
+    pub complete_internal_call_task: ManagedFunction<CompleteInternalCallTaskFn>,
+    pub fail_internal_call_task: ManagedFunction<FailInternalCallTaskFn>,
     pub load_assembly: ManagedFunction<LoadAssemblyFn>,
     pub load_assembly_from_memory: ManagedFunction<LoadAssemblyFromMemoryFn>,
+    pub load_assembly_from_memory_with_symbols:
+        ManagedFunction<LoadAssemblyFromMemoryWithSymbolsFn>,
+    pub get_shared_assembly_load_context_id: ManagedFunction<GetSharedAssemblyLoadContextIdFn>,
     pub unload_assembly_load_context: ManagedFunction<UnloadAssemblyLoadContextFn>,
+    pub add_probing_directory: ManagedFunction<AddProbingDirectoryFn>,
+    pub set_dependency_manifest: ManagedFunction<SetDependencyManifestFn>,
+    pub set_assembly_resolver: ManagedFunction<SetAssemblyResolverFn>,
+    pub compile_and_load_assembly: ManagedFunction<CompileAndLoadAssemblyFn>,
+    pub get_last_compiler_diagnostics: ManagedFunction<GetLastCompilerDiagnosticsFn>,
+    pub get_assembly_load_contexts: ManagedFunction<GetAssemblyLoadContextsFn>,
+    pub set_verification_policy: ManagedFunction<SetVerificationPolicyFn>,
+    pub get_last_verification_failure: ManagedFunction<GetLastVerificationFailureFn>,
+    pub set_sandbox_policy: ManagedFunction<SetSandboxPolicyFn>,
+    pub get_last_sandbox_findings: ManagedFunction<GetLastSandboxFindingsFn>,
     pub get_last_load_status: ManagedFunction<GetLastLoadStatusFn>,
     pub get_assembly_name: ManagedFunction<GetAssemblyNameFn>,
-    pub get_assembly_types: ManagedFunction<GetAssemblyTypesFn>,
+    pub get_assembly_version: ManagedFunction<GetAssemblyVersionFn>,
+    pub get_assembly_resource_names: ManagedFunction<GetAssemblyResourceNamesFn>,
+    pub read_assembly_resource: ManagedFunction<ReadAssemblyResourceFn>,
+    pub run_assembly_initializers: ManagedFunction<RunAssemblyInitializersFn>,
+    pub get_last_initializer_errors: ManagedFunction<GetLastInitializerErrorsFn>,
+    pub get_assembly_type_index: ManagedFunction<GetAssemblyTypeIndexFn>,
     pub get_type_id: ManagedFunction<GetTypeIdFn>,
     pub get_full_type_name: ManagedFunction<GetFullTypeNameFn>,
     pub get_assembly_qualified_name: ManagedFunction<GetAssemblyQualifiedNameFn>,
@@ -143,6 +240,7 @@ pub struct CoralManagedFunctions {
 
     pub get_attribute_field_value: ManagedFunction<GetAttributeFieldValueFn>,
     pub get_attribute_type: ManagedFunction<GetAttributeTypeFn>,
+    pub destroy_attribute: ManagedFunction<DestroyAttributeFn>,
 
     pub create_object: ManagedFunction<CreateObjectFn>,
     pub create_assembly_load_context: ManagedFunction<CreateAssemblyLoadContextFn>,
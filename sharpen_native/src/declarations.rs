@@ -0,0 +1,368 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use crate::{
+    AsyncInternalClosure, InternalCallFn, InternalClosure,
+    internal_call::{InternalCallRegistration, InternalCallSignature},
+    managed_type::ManagedType,
+};
+
+#[derive(Debug)]
+pub enum DeclarationError {
+    /// A parameter or the return type has no C# type Sharpen can name (e.g a struct or a slice), so the field has to be
+    /// declared by hand
+    UnsupportedType {
+        class_name: String,
+        variable_name: String,
+    },
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for DeclarationError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+struct Declaration {
+    signature: InternalCallSignature,
+    is_closure: bool,
+}
+
+/// Collects internal calls the same way [`crate::assembly::ManagedAssembly`] does, and generates the C# partial classes
+/// that declare their `delegate* unmanaged<...>` fields, so the two sides can't drift.
+/// The classes in the assembly have to be `partial` (and must not declare the fields themselves):
+/// ```ignore
+/// let mut declarations = InternalCallDeclarations::new();
+/// declarations.register(&LOG_MESSAGE);
+/// declarations.write("Scripts/Generated/InternalCalls.g.cs")?;
+/// ```
+#[derive(Default)]
+pub struct InternalCallDeclarations {
+    /// Sorted by class and then field, so the generated file only changes when the calls do
+    classes: BTreeMap<String, BTreeMap<String, Declaration>>,
+}
+
+impl InternalCallDeclarations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a field for [`crate::assembly::ManagedAssembly::add_internal_call`]
+    pub fn add<F: InternalCallFn>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        _function: F,
+    ) -> &mut Self {
+        self.push(
+            class_name,
+            variable_name,
+            InternalCallSignature::of::<F>(),
+            false,
+        )
+    }
+
+    /// Declares the fields for [`crate::assembly::ManagedAssembly::add_internal_closure`], including its `Context` field
+    pub fn add_closure<Args, F: InternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        _closure: &F,
+    ) -> &mut Self {
+        self.push(
+            class_name,
+            variable_name,
+            InternalCallSignature::of_closure::<Args, F>(),
+            true,
+        )
+    }
+
+    /// Declares the fields for [`crate::assembly::ManagedAssembly::add_async_internal_call`], including its `Context` field
+    pub fn add_async<Args, F: AsyncInternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        _closure: &F,
+    ) -> &mut Self {
+        self.push(
+            class_name,
+            variable_name,
+            InternalCallSignature::of_async::<Args, F>(),
+            true,
+        )
+    }
+
+    /// Declares a field for an internal call generated by [`crate::internal_call`]
+    pub fn register(&mut self, registration: &InternalCallRegistration) -> &mut Self {
+        self.push(
+            registration.class_name,
+            registration.variable_name,
+            (registration.signature)(),
+            false,
+        )
+    }
+
+    fn push(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        signature: InternalCallSignature,
+        is_closure: bool,
+    ) -> &mut Self {
+        self.classes
+            .entry(class_name.to_string())
+            .or_default()
+            .insert(
+                variable_name.to_string(),
+                Declaration {
+                    signature,
+                    is_closure,
+                },
+            );
+
+        self
+    }
+
+    /// The C# source of every declared class
+    pub fn generate(&self) -> Result<String, DeclarationError> {
+        let mut source = String::new();
+        source.push_str("// <auto-generated>\n");
+        source.push_str("//     Generated by Sharpen from the registered internal calls, don't edit it by hand.\n");
+        source.push_str("// </auto-generated>\n");
+
+        for (class_name, fields) in &self.classes {
+            // NOTE: Nested classes (`Outer+Inner`) are declared inside partial declarations of the classes around them
+            let (outer_class, nested_classes) = match class_name.split_once('+') {
+                Some((outer_class, nested_classes)) => (outer_class, Some(nested_classes)),
+                None => (class_name.as_str(), None),
+            };
+            let (namespace, outer_class) = match outer_class.rsplit_once('.') {
+                Some((namespace, outer_class)) => (Some(namespace), outer_class),
+                None => (None, outer_class),
+            };
+            let classes = std::iter::once(outer_class)
+                .chain(
+                    nested_classes
+                        .into_iter()
+                        .flat_map(|nested| nested.split('+')),
+                )
+                .collect::<Vec<_>>();
+            let depth = usize::from(namespace.is_some());
+            let indent = "\t".repeat(depth + classes.len());
+
+            source.push('\n');
+            if let Some(namespace) = namespace {
+                let _ = writeln!(source, "namespace {namespace}\n{{");
+            }
+            for (nesting, class) in classes.iter().enumerate() {
+                let class_indent = "\t".repeat(depth + nesting);
+                let _ = writeln!(
+                    source,
+                    "{class_indent}unsafe partial class {class}\n{class_indent}{{"
+                );
+            }
+
+            for (variable_name, declaration) in fields {
+                let signature = &declaration.signature;
+                let unsupported = || DeclarationError::UnsupportedType {
+                    class_name: class_name.clone(),
+                    variable_name: variable_name.clone(),
+                };
+
+                let mut types = signature
+                    .parameter_types
+                    .iter()
+                    .map(|&r#type| csharp_type_name(r#type, false).ok_or_else(unsupported))
+                    .collect::<Result<Vec<_>, _>>()?;
+                types.push(csharp_type_name(signature.return_type, true).ok_or_else(unsupported)?);
+
+                let _ = writeln!(
+                    source,
+                    "{indent}internal static delegate* unmanaged<{}> {variable_name};",
+                    types.join(", ")
+                );
+                if declaration.is_closure {
+                    let _ = writeln!(
+                        source,
+                        "{indent}internal static System.IntPtr {variable_name}Context;"
+                    );
+                }
+            }
+
+            for nesting in (0..classes.len()).rev() {
+                let _ = writeln!(source, "{}}}", "\t".repeat(depth + nesting));
+            }
+            if namespace.is_some() {
+                source.push_str("}\n");
+            }
+        }
+
+        Ok(source)
+    }
+
+    /// Writes the generated source to `path`, unless it already contains it (so builds that watch it aren't retriggered)
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), DeclarationError> {
+        let path = path.as_ref();
+        let source = self.generate()?;
+
+        if std::fs::read_to_string(path).is_ok_and(|existing| existing == source) {
+            return Ok(());
+        }
+
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        std::fs::write(path, source)?;
+
+        Ok(())
+    }
+}
+
+fn csharp_type_name(r#type: ManagedType, is_return_type: bool) -> Option<&'static str> {
+    let name = match r#type {
+        ManagedType::SByte => "sbyte",
+        ManagedType::Byte => "byte",
+        ManagedType::Short => "short",
+        ManagedType::UShort => "ushort",
+        ManagedType::Int => "int",
+        ManagedType::UInt => "uint",
+        ManagedType::Long => "long",
+        ManagedType::ULong => "ulong",
+        ManagedType::Float => "float",
+        ManagedType::Double => "double",
+        ManagedType::Bool => "bool",
+        ManagedType::Bool32 => "Coral.Managed.Interop.Bool32",
+        ManagedType::String => "Coral.Managed.Interop.NativeString",
+        ManagedType::Pointer => "System.IntPtr",
+        ManagedType::Void if is_return_type => "void",
+        ManagedType::Void | ManagedType::Unknown => return None,
+    };
+
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FfiSafe;
+
+    #[repr(C)]
+    struct Vector3 {
+        _x: f32,
+        _y: f32,
+        _z: f32,
+    }
+
+    unsafe impl FfiSafe for Vector3 {}
+
+    extern "system" fn scale(value: f32, factor: f32) -> f32 {
+        value * factor
+    }
+
+    extern "system" fn length(_vector: Vector3) -> f32 {
+        0.0
+    }
+
+    const HEADER: &str = "// <auto-generated>\n//     Generated by Sharpen from the registered internal calls, don't edit it by hand.\n// </auto-generated>\n";
+
+    #[test]
+    fn function_and_closure_fields() {
+        let mut declarations = InternalCallDeclarations::new();
+        declarations
+            .add(
+                "Example.Managed.Math",
+                "Scale",
+                scale as extern "system" fn(f32, f32) -> f32,
+            )
+            .add_closure("Example.Managed.Math", "Count", &|value: i32| value + 1);
+
+        assert_eq!(
+            declarations.generate().unwrap(),
+            format!(
+                "{HEADER}
+namespace Example.Managed
+{{
+\tunsafe partial class Math
+\t{{
+\t\tinternal static delegate* unmanaged<System.IntPtr, int, int> Count;
+\t\tinternal static System.IntPtr CountContext;
+\t\tinternal static delegate* unmanaged<float, float, float> Scale;
+\t}}
+}}
+"
+            )
+        );
+    }
+
+    #[test]
+    fn nested_classes_without_namespace() {
+        let mut declarations = InternalCallDeclarations::new();
+        declarations.add(
+            "Outer+Inner",
+            "Scale",
+            scale as extern "system" fn(f32, f32) -> f32,
+        );
+
+        assert_eq!(
+            declarations.generate().unwrap(),
+            format!(
+                "{HEADER}
+unsafe partial class Outer
+{{
+\tunsafe partial class Inner
+\t{{
+\t\tinternal static delegate* unmanaged<float, float, float> Scale;
+\t}}
+}}
+"
+            )
+        );
+    }
+
+    #[test]
+    fn nested_classes_in_namespace() {
+        let mut declarations = InternalCallDeclarations::new();
+        declarations.add(
+            "Example.Managed.Outer+Middle+Inner",
+            "Scale",
+            scale as extern "system" fn(f32, f32) -> f32,
+        );
+
+        assert_eq!(
+            declarations.generate().unwrap(),
+            format!(
+                "{HEADER}
+namespace Example.Managed
+{{
+\tunsafe partial class Outer
+\t{{
+\t\tunsafe partial class Middle
+\t\t{{
+\t\t\tunsafe partial class Inner
+\t\t\t{{
+\t\t\t\tinternal static delegate* unmanaged<float, float, float> Scale;
+\t\t\t}}
+\t\t}}
+\t}}
+}}
+"
+            )
+        );
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let mut declarations = InternalCallDeclarations::new();
+        declarations.add(
+            "Example.Managed.Math",
+            "Length",
+            length as extern "system" fn(Vector3) -> f32,
+        );
+
+        assert!(matches!(
+            declarations.generate(),
+            Err(DeclarationError::UnsupportedType { class_name, variable_name })
+                if class_name == "Example.Managed.Math" && variable_name == "Length"
+        ));
+    }
+}
@@ -1,10 +1,16 @@
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::{
+    any::Any,
+    sync::{Arc, Mutex, MutexGuard},
+};
 
 use netcorehost::{hostfxr, nethost, pdcstr, pdcstring};
 
 use crate::{
-    assembly::AssemblyLoadContext,
+    assembly::{AssemblyLoadContext, AssemblyLoadError, ManagedAssembly},
+    async_internal_call::InternalCallExecutor,
+    context_info::{AssemblyLoadContextInfo, snapshot_contexts},
     coral_managed_fns::*,
+    internal_call::InternalCallRegistry,
     message_level::{MessageCallbackFn, MessageCallbackFnInternal, MessageLevel},
     string::{CSharpNativeString, ScopedCSharpNativeString},
     type_cache::TypeCache,
@@ -46,6 +52,8 @@ pub struct HostInstance {
 
     managed_functions: Arc<CoralManagedFunctions>,
     type_cache: Arc<Mutex<TypeCache>>,
+    internal_calls: Arc<Mutex<InternalCallRegistry>>,
+    internal_call_executor: Arc<Mutex<Option<Arc<dyn InternalCallExecutor>>>>,
 }
 
 impl HostInstance {
@@ -81,6 +89,8 @@ impl HostInstance {
 
             managed_functions,
             type_cache: Arc::new(Mutex::new(TypeCache::new())),
+            internal_calls: Arc::new(Mutex::new(InternalCallRegistry::new())),
+            internal_call_executor: Arc::new(Mutex::new(None)),
         })
     }
 
@@ -93,13 +103,65 @@ impl HostInstance {
         )
     }
 
+    /// Loads an assembly (e.g the engine API) into the host-wide shared context. Every context created by
+    /// [`HostInstance::create_assembly_load_context`] resolves it from there instead of loading its own copy,
+    /// so its types have one identity and objects can be passed between contexts. The shared context is never unloaded.
+    pub fn load_shared_assembly(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<Arc<ManagedAssembly>, AssemblyLoadError> {
+        let path = ScopedCSharpNativeString::from_str(&path.to_string_lossy());
+
+        let context_id = (self.managed_functions.get_shared_assembly_load_context_id)();
+        let assembly_id = (self.managed_functions.load_assembly)(context_id, path.inner());
+        let assembly = ManagedAssembly::load(self, assembly_id, None)?;
+
+        let mut type_cache = self.type_cache();
+        for r#type in assembly.types() {
+            type_cache.mark_shared(r#type.id);
+        }
+
+        Ok(assembly)
+    }
+
+    /// Every live managed context (including the shared one), and the ones that were unloaded but not collected yet
+    pub fn contexts(&self) -> Vec<AssemblyLoadContextInfo> {
+        snapshot_contexts(self)
+    }
+
     pub fn type_cache(&self) -> MutexGuard<TypeCache> {
         self.type_cache.lock().expect("TypeCache Mutex is poisoned")
     }
 
+    /// Internal calls that are bound to every assembly loaded from now on that defines their class, in any context
+    /// (including the shared one), e.g `host.internal_calls().register(&LOG_MESSAGE)`. A call the context's own
+    /// registry also has replaces this one, see [`AssemblyLoadContext::set_internal_calls`].
+    pub fn internal_calls(&self) -> MutexGuard<'_, InternalCallRegistry> {
+        self.internal_calls
+            .lock()
+            .expect("InternalCallRegistry Mutex is poisoned")
+    }
+
+    /// Runs the futures of async internal calls (see [`ManagedAssembly::add_async_internal_call`]), e.g on the engine's
+    /// job system. Until one is set, async internal calls fail their task.
+    pub fn set_internal_call_executor(&self, executor: impl InternalCallExecutor) {
+        *self
+            .internal_call_executor
+            .lock()
+            .expect("InternalCallExecutor Mutex is poisoned") = Some(Arc::new(executor));
+    }
+
+    pub(crate) fn internal_call_executor(&self) -> Option<Arc<dyn InternalCallExecutor>> {
+        self.internal_call_executor
+            .lock()
+            .expect("InternalCallExecutor Mutex is poisoned")
+            .clone()
+    }
+
     /// Automatically called when AssemblyLoadContext is dropped
     pub(crate) fn unload_assembly_load_context(&self, assembly_load_context: &AssemblyLoadContext) {
         (self.managed_functions.unload_assembly_load_context)(assembly_load_context.context_id());
+        self.type_cache().evict_unloaded();
     }
 
     pub(crate) fn managed_functions(&self) -> &CoralManagedFunctions {
@@ -114,6 +176,24 @@ static mut MESSAGE_CALLBACK: MessageCallbackFn = |_, _| {
 static mut MESSAGE_FILTER: MessageLevel = MessageLevel::Info;
 static mut EXCEPTION_CALLBACK: Option<ExceptionCallbackFn> = None;
 
+/// Reports a message from the Rust side through the host's message callback
+pub(crate) fn log_message(message: String, level: MessageLevel) {
+    unsafe {
+        MESSAGE_CALLBACK(message, level);
+    }
+}
+
+/// The message a panic was started with, for reporting panics caught at the FFI boundary
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
 #[inline]
 extern "system" fn message_callback(in_message: CSharpNativeString, in_level: MessageLevel) {
     let message = in_message.to_string();
@@ -198,6 +278,54 @@ impl HostInstance {
                 pdcstr!("Coral.Managed.Interop.InternalCallsManager, Coral.Managed"),
                 pdcstr!("SetInternalCalls"),
             )?;
+        let get_last_internal_call_errors = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastInternalCallErrorsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallsManager, Coral.Managed"),
+                pdcstr!("GetLastInternalCallErrors"),
+            )?;
+        let audit_internal_calls = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<AuditInternalCallsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallsManager, Coral.Managed"),
+                pdcstr!("AuditInternalCalls"),
+            )?;
+        let unbind_internal_closures = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<UnbindInternalClosuresFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallsManager, Coral.Managed"),
+                pdcstr!("UnbindInternalClosures"),
+            )?;
+        let pause_internal_calls = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<PauseInternalCallsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallPauses, Coral.Managed"),
+                pdcstr!("PauseInternalCalls"),
+            )?;
+        let resume_internal_calls = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<ResumeInternalCallsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallPauses, Coral.Managed"),
+                pdcstr!("ResumeInternalCalls"),
+            )?;
+        let get_last_unbound_internal_calls = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastUnboundInternalCallsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallsManager, Coral.Managed"),
+                pdcstr!("GetLastUnboundInternalCalls"),
+            )?;
+        let complete_internal_call_task = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<CompleteInternalCallTaskFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallTasks, Coral.Managed"),
+                pdcstr!("CompleteInternalCallTask"),
+            )?;
+        let fail_internal_call_task = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<FailInternalCallTaskFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.Interop.InternalCallTasks, Coral.Managed"),
+                pdcstr!("FailInternalCallTask"),
+            )?;
         let load_assembly = delegate_loader
             .load_assembly_and_get_function_with_unmanaged_callers_only::<LoadAssemblyFn>(
                 assembly_path,
@@ -210,12 +338,84 @@ impl HostInstance {
                 pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
                 pdcstr!("LoadAssemblyFromMemory"),
             )?;
+        let load_assembly_from_memory_with_symbols = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<LoadAssemblyFromMemoryWithSymbolsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("LoadAssemblyFromMemoryWithSymbols"),
+            )?;
+        let get_shared_assembly_load_context_id = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetSharedAssemblyLoadContextIdFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetSharedAssemblyLoadContextId"),
+            )?;
         let unload_assembly_load_context = delegate_loader
             .load_assembly_and_get_function_with_unmanaged_callers_only::<UnloadAssemblyLoadContextFn>(
                 assembly_path,
                 pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
                 pdcstr!("UnloadAssemblyLoadContext"),
             )?;
+        let add_probing_directory = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<AddProbingDirectoryFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("AddProbingDirectory"),
+            )?;
+        let set_dependency_manifest = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<SetDependencyManifestFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("SetDependencyManifest"),
+            )?;
+        let set_assembly_resolver = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<SetAssemblyResolverFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("SetAssemblyResolver"),
+            )?;
+        let compile_and_load_assembly = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<CompileAndLoadAssemblyFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("CompileAndLoadAssembly"),
+            )?;
+        let get_last_compiler_diagnostics = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastCompilerDiagnosticsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.RuntimeCompiler, Coral.Managed"),
+                pdcstr!("GetLastCompilerDiagnostics"),
+            )?;
+        let get_assembly_load_contexts = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetAssemblyLoadContextsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetAssemblyLoadContexts"),
+            )?;
+        let set_verification_policy = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<SetVerificationPolicyFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("SetVerificationPolicy"),
+            )?;
+        let get_last_verification_failure = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastVerificationFailureFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetLastVerificationFailure"),
+            )?;
+        let set_sandbox_policy = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<SetSandboxPolicyFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("SetSandboxPolicy"),
+            )?;
+        let get_last_sandbox_findings = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastSandboxFindingsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetLastSandboxFindings"),
+            )?;
         let get_last_load_status = delegate_loader
             .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastLoadStatusFn>(
                 assembly_path,
@@ -228,12 +428,42 @@ impl HostInstance {
                 pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
                 pdcstr!("GetAssemblyName"),
             )?;
+        let get_assembly_version = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetAssemblyVersionFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetAssemblyVersion"),
+            )?;
+        let get_assembly_resource_names = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetAssemblyResourceNamesFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("GetAssemblyResourceNames"),
+            )?;
+        let read_assembly_resource = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<ReadAssemblyResourceFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyLoader, Coral.Managed"),
+                pdcstr!("ReadAssemblyResource"),
+            )?;
+        let run_assembly_initializers = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<RunAssemblyInitializersFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyInitializers, Coral.Managed"),
+                pdcstr!("RunAssemblyInitializers"),
+            )?;
+        let get_last_initializer_errors = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetLastInitializerErrorsFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.AssemblyInitializers, Coral.Managed"),
+                pdcstr!("GetLastInitializerErrors"),
+            )?;
 
-        let get_assembly_types = delegate_loader
-            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetAssemblyTypesFn>(
+        let get_assembly_type_index = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<GetAssemblyTypeIndexFn>(
                 assembly_path,
                 pdcstr!("Coral.Managed.TypeInterface, Coral.Managed"),
-                pdcstr!("GetAssemblyTypes"),
+                pdcstr!("GetAssemblyTypeIndex"),
             )?;
         let get_type_id = delegate_loader
             .load_assembly_and_get_function_with_unmanaged_callers_only::<GetTypeIdFn>(
@@ -444,6 +674,12 @@ impl HostInstance {
                 pdcstr!("Coral.Managed.TypeInterface, Coral.Managed"),
                 pdcstr!("GetAttributeType"),
             )?;
+        let destroy_attribute = delegate_loader
+            .load_assembly_and_get_function_with_unmanaged_callers_only::<DestroyAttributeFn>(
+                assembly_path,
+                pdcstr!("Coral.Managed.TypeInterface, Coral.Managed"),
+                pdcstr!("DestroyAttribute"),
+            )?;
 
         let create_object = delegate_loader
             .load_assembly_and_get_function_with_unmanaged_callers_only::<CreateObjectFn>(
@@ -494,13 +730,38 @@ impl HostInstance {
             create_assembly_load_context,
 
             set_internal_calls,
+            get_last_internal_call_errors,
+            audit_internal_calls,
+            unbind_internal_closures,
+            pause_internal_calls,
+            resume_internal_calls,
+            get_last_unbound_internal_calls,
+            complete_internal_call_task,
+            fail_internal_call_task,
             load_assembly,
             load_assembly_from_memory,
+            load_assembly_from_memory_with_symbols,
+            get_shared_assembly_load_context_id,
             unload_assembly_load_context,
+            add_probing_directory,
+            set_dependency_manifest,
+            set_assembly_resolver,
+            compile_and_load_assembly,
+            get_last_compiler_diagnostics,
+            get_assembly_load_contexts,
+            set_verification_policy,
+            get_last_verification_failure,
+            set_sandbox_policy,
+            get_last_sandbox_findings,
             get_last_load_status,
             get_assembly_name,
+            get_assembly_version,
+            get_assembly_resource_names,
+            read_assembly_resource,
+            run_assembly_initializers,
+            get_last_initializer_errors,
 
-            get_assembly_types,
+            get_assembly_type_index,
             get_type_id,
             get_full_type_name,
             get_assembly_qualified_name,
@@ -540,6 +801,7 @@ impl HostInstance {
 
             get_attribute_field_value,
             get_attribute_type,
+            destroy_attribute,
 
             create_object,
 
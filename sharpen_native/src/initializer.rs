@@ -0,0 +1,109 @@
+use crate::{
+    host_instance::HostInstance,
+    managed_object::ManagedObject,
+    string::{CSharpNativeString, ScopedCSharpNativeString},
+};
+
+/// What the initializers of an assembly are called with, see [`crate::assembly::AssemblyLoadContext::set_initializers`].
+/// Initializers that take no parameters ignore it.
+pub enum InitializerArgument {
+    None,
+    /// Passed as the object itself. The context owns it from now on and destroys it when it's dropped.
+    Object(ManagedObject),
+    /// Passed as an `IntPtr` or pointer parameter
+    Pointer(*mut std::ffi::c_void),
+}
+
+/// An initializer that threw
+#[derive(Debug, Clone)]
+pub struct InitializerError {
+    /// Full name of the initializer (e.g `MyGame.Scripts.Startup.Init`)
+    pub method: String,
+    /// The exception it threw, including the stack trace
+    pub message: String,
+}
+
+pub(crate) struct InitializerSettings {
+    attribute: String,
+    argument: InitializerArgument,
+}
+
+impl InitializerSettings {
+    pub(crate) fn new(attribute: &str, argument: InitializerArgument) -> Self {
+        Self {
+            attribute: attribute.to_string(),
+            argument,
+        }
+    }
+
+    fn native_argument(&self) -> (*mut std::ffi::c_void, bool) {
+        match &self.argument {
+            InitializerArgument::None => (std::ptr::null_mut(), false),
+            InitializerArgument::Object(object) => (object.handle, true),
+            InitializerArgument::Pointer(pointer) => (*pointer, false),
+        }
+    }
+}
+
+impl Drop for InitializerSettings {
+    fn drop(&mut self) {
+        if let InitializerArgument::Object(object) =
+            std::mem::replace(&mut self.argument, InitializerArgument::None)
+        {
+            object.destroy();
+        }
+    }
+}
+
+#[repr(C)]
+pub(crate) struct NativeInitializerError {
+    method: CSharpNativeString,
+    message: CSharpNativeString,
+}
+
+/// Runs the initializers of the assembly and returns the ones that failed
+pub(crate) fn run_initializers(
+    host: &HostInstance,
+    assembly_id: i32,
+    settings: &InitializerSettings,
+) -> Vec<InitializerError> {
+    let managed_functions = host.managed_functions();
+    let attribute = ScopedCSharpNativeString::from_str(&settings.attribute);
+    let (argument, argument_is_object) = settings.native_argument();
+
+    let error_count = (managed_functions.run_assembly_initializers)(
+        assembly_id,
+        attribute.inner(),
+        argument,
+        argument_is_object.into(),
+    );
+    if error_count == 0 {
+        return vec![];
+    }
+
+    let get_last_initializer_errors = &managed_functions.get_last_initializer_errors;
+
+    let mut error_count = 0i32;
+    get_last_initializer_errors(std::ptr::null_mut(), &mut error_count);
+
+    let mut errors = Vec::<NativeInitializerError>::with_capacity(error_count as usize);
+    get_last_initializer_errors(errors.as_mut_ptr(), &mut error_count);
+    unsafe {
+        errors.set_len(error_count as usize);
+    }
+
+    let result = errors
+        .iter()
+        .map(|error| InitializerError {
+            method: error.method.to_string(),
+            message: error.message.to_string(),
+        })
+        .collect();
+
+    for error in &mut errors {
+        CSharpNativeString::free(&mut error.method);
+        CSharpNativeString::free(&mut error.message);
+    }
+
+    result
+}
@@ -0,0 +1,743 @@
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use netcorehost::pdcstring;
+
+use crate::{
+    AsyncInternalClosure, Bool32, InternalCall,
+    async_internal_call::AsyncInternalCallContext,
+    host_instance::{HostInstance, log_message},
+    managed_object::ManagedObject,
+    managed_type::{FfiSafe, ManagedType},
+    message_level::MessageLevel,
+    string::CSharpNativeString,
+};
+
+/// The host that last bound a call generated by [`crate::internal_call`] (which its `ManagedObject` parameters belong to),
+/// or an async internal call (which completes its task through it).
+/// The shims have no state, so the macro declares one next to every shim.
+pub struct InternalCallHost(RwLock<Option<HostInstance>>);
+
+impl InternalCallHost {
+    pub const fn new() -> Self {
+        Self(RwLock::new(None))
+    }
+
+    pub(crate) fn set(&self, host: &HostInstance) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(host.clone());
+    }
+
+    pub fn get(&self) -> Option<HostInstance> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl Default for InternalCallHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a bound call keeps the host that bound it
+#[derive(Clone)]
+pub(crate) enum InternalCallHostSlot {
+    /// Declared next to a shim generated by [`crate::internal_call`]
+    Static(&'static InternalCallHost),
+    /// Shared with the context of an async internal call
+    Shared(Arc<InternalCallHost>),
+}
+
+impl InternalCallHostSlot {
+    fn set(&self, host: &HostInstance) {
+        match self {
+            Self::Static(slot) => slot.set(host),
+            Self::Shared(slot) => slot.set(host),
+        }
+    }
+}
+
+/// An `extern "system"` function that can be bound to a C# `delegate* unmanaged<...>` field with
+/// [`crate::assembly::ManagedAssembly::add_internal_call`]. Implemented for functions with up to 8 parameters.
+pub trait InternalCallFn: Copy {
+    fn parameter_types() -> Vec<ManagedType>;
+    fn return_type() -> ManagedType;
+    fn as_ptr(self) -> *const std::ffi::c_void;
+}
+
+macro_rules! impl_internal_call_fn {
+    ($($arg:ident),*) => {
+        impl<Ret: FfiSafe, $($arg: FfiSafe),*> InternalCallFn for extern "system" fn($($arg),*) -> Ret {
+            fn parameter_types() -> Vec<ManagedType> {
+                vec![$($arg::MANAGED_TYPE),*]
+            }
+
+            fn return_type() -> ManagedType {
+                Ret::MANAGED_TYPE
+            }
+
+            fn as_ptr(self) -> *const std::ffi::c_void {
+                self as *const std::ffi::c_void
+            }
+        }
+    };
+}
+
+impl_internal_call_fn!();
+impl_internal_call_fn!(A);
+impl_internal_call_fn!(A, B);
+impl_internal_call_fn!(A, B, C);
+impl_internal_call_fn!(A, B, C, D);
+impl_internal_call_fn!(A, B, C, D, E);
+impl_internal_call_fn!(A, B, C, D, E, F);
+impl_internal_call_fn!(A, B, C, D, E, F, G);
+impl_internal_call_fn!(A, B, C, D, E, F, G, H);
+
+/// A closure that can be bound with [`crate::assembly::ManagedAssembly::add_internal_closure`]. Implemented for closures
+/// with up to 8 parameters.
+pub trait InternalClosure<Args>: Send + Sync + 'static {
+    /// Includes the context pointer C# passes back as the first argument
+    fn parameter_types() -> Vec<ManagedType>;
+    fn return_type() -> ManagedType;
+    /// An `extern "system"` function that calls the closure its context points to
+    fn trampoline() -> *const std::ffi::c_void;
+}
+
+macro_rules! impl_internal_closure {
+    ($($arg:ident),*) => {
+        impl<Func, Ret: FfiSafe, $($arg: FfiSafe),*> InternalClosure<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Ret + Send + Sync + 'static,
+        {
+            fn parameter_types() -> Vec<ManagedType> {
+                vec![ManagedType::Pointer, $($arg::MANAGED_TYPE),*]
+            }
+
+            fn return_type() -> ManagedType {
+                Ret::MANAGED_TYPE
+            }
+
+            fn trampoline() -> *const std::ffi::c_void {
+                #[allow(non_snake_case)]
+                extern "system" fn trampoline<Func: Fn($($arg),*) -> Ret, Ret, $($arg),*>(
+                    context: *const std::ffi::c_void,
+                    $($arg: $arg),*
+                ) -> Ret {
+                    let closure = unsafe { &*(context as *const Func) };
+                    closure($($arg),*)
+                }
+
+                trampoline::<Func, Ret, $($arg),*> as *const std::ffi::c_void
+            }
+        }
+    };
+}
+
+impl_internal_closure!();
+impl_internal_closure!(A);
+impl_internal_closure!(A, B);
+impl_internal_closure!(A, B, C);
+impl_internal_closure!(A, B, C, D);
+impl_internal_closure!(A, B, C, D, E);
+impl_internal_closure!(A, B, C, D, E, F);
+impl_internal_closure!(A, B, C, D, E, F, G);
+impl_internal_closure!(A, B, C, D, E, F, G, H);
+
+/// Why an internal call couldn't be bound, see [`crate::assembly::ManagedAssembly::upload_internal_calls`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InternalCallErrorReason {
+    InvalidName,
+    TypeNotFound,
+    FieldNotFound,
+    /// The field isn't a `delegate* unmanaged<...>`, managed function pointers use a different calling convention
+    NotAFunctionPointer,
+    /// The native function's signature doesn't match the field's
+    SignatureMismatch,
+    /// A closure's class has no static `IntPtr` field named `{variable_name}Context`
+    ContextFieldNotFound,
+    /// Binding the call threw, the message has the exception
+    Exception,
+}
+
+/// An internal call that couldn't be bound
+#[derive(Debug, Clone)]
+pub struct InternalCallError {
+    pub class_name: String,
+    pub variable_name: String,
+    pub reason: InternalCallErrorReason,
+    /// What was logged, e.g which types don't match
+    pub message: String,
+}
+
+#[repr(C)]
+pub(crate) struct NativeInternalCallError {
+    class_name: CSharpNativeString,
+    variable_name: CSharpNativeString,
+    reason: InternalCallErrorReason,
+    message: CSharpNativeString,
+}
+
+pub(crate) fn last_internal_call_errors(host: &HostInstance) -> Vec<InternalCallError> {
+    let get_last_internal_call_errors = &host.managed_functions().get_last_internal_call_errors;
+
+    let mut error_count = 0i32;
+    get_last_internal_call_errors(std::ptr::null_mut(), &mut error_count);
+
+    let mut errors = Vec::<NativeInternalCallError>::with_capacity(error_count as usize);
+    get_last_internal_call_errors(errors.as_mut_ptr(), &mut error_count);
+    unsafe {
+        errors.set_len(error_count as usize);
+    }
+
+    let result = errors
+        .iter()
+        .map(|error| InternalCallError {
+            class_name: error.class_name.to_string(),
+            variable_name: error.variable_name.to_string(),
+            reason: error.reason,
+            message: error.message.to_string(),
+        })
+        .collect();
+
+    for error in &mut errors {
+        CSharpNativeString::free(&mut error.class_name);
+        CSharpNativeString::free(&mut error.variable_name);
+        CSharpNativeString::free(&mut error.message);
+    }
+
+    result
+}
+
+/// Internal calls in the layout `SetInternalCalls` expects, together with everything they point to
+#[derive(Default)]
+pub(crate) struct InternalCallTable {
+    keys: Vec<(String, String)>,
+    name_storage: Vec<pdcstring::PdCString>,
+    signature_storage: Vec<Vec<ManagedType>>,
+    // TODO: Have a C# compatible InternalCall def and a proper rust one without actual pointers
+    calls: Vec<InternalCall>,
+    closures: Vec<Arc<dyn Any + Send + Sync>>,
+    hosts: Vec<InternalCallHostSlot>,
+}
+
+impl InternalCallTable {
+    pub(crate) fn push(
+        &mut self,
+        assembly_name: &str,
+        class_name: &str,
+        variable_name: &str,
+        call: RegisteredInternalCall,
+    ) {
+        let assembly_qualified_name = format!("{class_name}+{variable_name}, {assembly_name}");
+        let name = pdcstring::PdCString::from_str(&assembly_qualified_name).unwrap();
+
+        // NOTE: Without a signature C# doesn't validate the call
+        let (parameter_types, parameter_count, return_type, reports_errors) = match call.signature {
+            Some(signature) => {
+                let native_signature = (
+                    signature.parameter_types.as_ptr(),
+                    signature.parameter_types.len() as i32,
+                    signature.return_type,
+                    signature.reports_errors,
+                );
+                self.signature_storage.push(signature.parameter_types);
+                native_signature
+            }
+            None => (std::ptr::null(), -1, ManagedType::Unknown, false),
+        };
+
+        // NOTE: The closure is behind an Arc, so the context pointer C# passes back stays valid
+        let context = match call.closure {
+            Some(closure) => {
+                let context = Arc::as_ptr(&closure) as *const std::ffi::c_void;
+                self.closures.push(closure);
+                context
+            }
+            None => std::ptr::null(),
+        };
+
+        self.calls.push(InternalCall {
+            name: name.as_ptr(),
+            native_function_ptr: call.function,
+            parameter_types,
+            parameter_count,
+            return_type,
+            context,
+            reports_errors: reports_errors.into(),
+            _padding: 0,
+        });
+        self.hosts.extend(call.host);
+        self.name_storage.push(name);
+        self.keys
+            .push((class_name.to_string(), variable_name.to_string()));
+    }
+
+    /// The class and field of every call in the table
+    pub(crate) fn keys(&self) -> &[(String, String)] {
+        &self.keys
+    }
+
+    pub(crate) fn has_closures(&self) -> bool {
+        !self.closures.is_empty()
+    }
+
+    pub(crate) fn upload(&self, host: &HostInstance) -> Result<(), Vec<InternalCallError>> {
+        for call_host in &self.hosts {
+            call_host.set(host);
+        }
+
+        let error_count = (host.managed_functions().set_internal_calls)(
+            self.calls.as_ptr() as *mut _,
+            self.calls.len() as i32,
+        );
+        if error_count == 0 {
+            return Ok(());
+        }
+
+        Err(last_internal_call_errors(host))
+    }
+}
+
+/// A static `delegate*` field that no native function was bound to, see [`crate::assembly::ManagedAssembly::unbound_internal_calls`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnboundInternalCall {
+    pub class_name: String,
+    pub variable_name: String,
+}
+
+#[repr(C)]
+pub(crate) struct NativeUnboundInternalCall {
+    class_name: CSharpNativeString,
+    variable_name: CSharpNativeString,
+}
+
+/// Finds the unbound internal call fields of the assembly, and fills them with stubs that throw if `bind_stubs` is set
+pub(crate) fn audit_internal_calls(
+    host: &HostInstance,
+    assembly_id: i32,
+    bind_stubs: bool,
+) -> Vec<UnboundInternalCall> {
+    let managed_functions = host.managed_functions();
+
+    let unbound_count = (managed_functions.audit_internal_calls)(assembly_id, bind_stubs.into());
+    if unbound_count == 0 {
+        return vec![];
+    }
+
+    let get_last_unbound_internal_calls = &managed_functions.get_last_unbound_internal_calls;
+
+    let mut unbound_count = 0i32;
+    get_last_unbound_internal_calls(std::ptr::null_mut(), &mut unbound_count);
+
+    let mut unbound_calls = Vec::<NativeUnboundInternalCall>::with_capacity(unbound_count as usize);
+    get_last_unbound_internal_calls(unbound_calls.as_mut_ptr(), &mut unbound_count);
+    unsafe {
+        unbound_calls.set_len(unbound_count as usize);
+    }
+
+    let result = unbound_calls
+        .iter()
+        .map(|unbound_call| UnboundInternalCall {
+            class_name: unbound_call.class_name.to_string(),
+            variable_name: unbound_call.variable_name.to_string(),
+        })
+        .collect();
+
+    for unbound_call in &mut unbound_calls {
+        CSharpNativeString::free(&mut unbound_call.class_name);
+        CSharpNativeString::free(&mut unbound_call.variable_name);
+    }
+
+    result
+}
+
+/// The native signature of an internal call, which [`crate::declarations::InternalCallDeclarations`] declares the C# field from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalCallSignature {
+    pub(crate) parameter_types: Vec<ManagedType>,
+    pub(crate) return_type: ManagedType,
+    /// See [`InternalCallSignature::of_fallible`]
+    pub(crate) reports_errors: bool,
+}
+
+impl InternalCallSignature {
+    pub fn of<F: InternalCallFn>() -> Self {
+        Self {
+            parameter_types: F::parameter_types(),
+            return_type: F::return_type(),
+            reports_errors: false,
+        }
+    }
+
+    /// For a function whose last parameter is the `*mut CSharpNativeString` it reports errors through (see [`InternalCallResult`]).
+    /// C# doesn't declare that parameter, the call is bound through a stub that passes it and throws the error as an
+    /// `InternalCallFailedException`.
+    pub fn of_fallible<F: InternalCallFn>() -> Self {
+        let mut parameter_types = F::parameter_types();
+        parameter_types.pop();
+
+        Self {
+            parameter_types,
+            return_type: F::return_type(),
+            reports_errors: true,
+        }
+    }
+
+    pub fn of_closure<Args, F: InternalClosure<Args>>() -> Self {
+        Self {
+            parameter_types: F::parameter_types(),
+            return_type: F::return_type(),
+            reports_errors: false,
+        }
+    }
+
+    pub fn of_async<Args, F: AsyncInternalClosure<Args>>() -> Self {
+        Self {
+            parameter_types: F::parameter_types(),
+            return_type: ManagedType::Void,
+            reports_errors: false,
+        }
+    }
+}
+
+/// Generated by [`crate::internal_call`] for every annotated function, see [`crate::assembly::ManagedAssembly::register_internal_call`]
+pub struct InternalCallRegistration {
+    pub class_name: &'static str,
+    pub variable_name: &'static str,
+    /// The generated shim
+    pub function: fn() -> *const std::ffi::c_void,
+    /// Signature of the generated shim
+    pub signature: fn() -> InternalCallSignature,
+    /// Set to the host that binds the call, for the shim to convert its arguments with
+    pub host: fn() -> &'static InternalCallHost,
+}
+
+#[derive(Clone)]
+pub(crate) struct RegisteredInternalCall {
+    pub(crate) function: *const std::ffi::c_void,
+    pub(crate) signature: Option<InternalCallSignature>,
+    /// Kept alive for as long as any registry or assembly still uses it
+    pub(crate) closure: Option<Arc<dyn Any + Send + Sync>>,
+    pub(crate) host: Option<InternalCallHostSlot>,
+}
+
+impl RegisteredInternalCall {
+    pub(crate) fn from_async<Args, F: AsyncInternalClosure<Args>>(closure: F) -> Self {
+        let context = Arc::new(AsyncInternalCallContext::new(closure));
+
+        Self {
+            function: F::trampoline(),
+            signature: Some(InternalCallSignature::of_async::<Args, F>()),
+            host: Some(InternalCallHostSlot::Shared(context.host.clone())),
+            closure: Some(context),
+        }
+    }
+}
+
+/// Internal calls that are bound to every assembly loaded afterwards, instead of to one [`crate::assembly::ManagedAssembly`] (which is
+/// shared behind an `Arc` once it's loaded). See [`crate::assembly::AssemblyLoadContext::set_internal_calls`] and
+/// [`crate::host_instance::HostInstance::internal_calls`].
+/// Calls are keyed by class and field, adding one twice replaces it.
+#[derive(Clone, Default)]
+pub struct InternalCallRegistry {
+    pub(crate) calls: BTreeMap<(String, String), RegisteredInternalCall>,
+}
+
+// NOTE: The pointers are to functions and to closures that are Send + Sync
+unsafe impl Send for InternalCallRegistry {}
+unsafe impl Sync for InternalCallRegistry {}
+
+impl InternalCallRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// See [`crate::assembly::ManagedAssembly::add_internal_call`]
+    pub fn add<F: InternalCallFn>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        function: F,
+    ) -> &mut Self {
+        self.insert(
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: function.as_ptr(),
+                signature: Some(InternalCallSignature::of::<F>()),
+                closure: None,
+                host: None,
+            },
+        )
+    }
+
+    /// See [`crate::assembly::ManagedAssembly::add_internal_closure`]
+    pub fn add_closure<Args, F: InternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        closure: F,
+    ) -> &mut Self {
+        self.insert(
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: F::trampoline(),
+                signature: Some(InternalCallSignature::of_closure::<Args, F>()),
+                closure: Some(Arc::new(closure)),
+                host: None,
+            },
+        )
+    }
+
+    /// See [`crate::assembly::ManagedAssembly::add_async_internal_call`]
+    pub fn add_async<Args, F: AsyncInternalClosure<Args>>(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        closure: F,
+    ) -> &mut Self {
+        self.insert(
+            class_name,
+            variable_name,
+            RegisteredInternalCall::from_async(closure),
+        )
+    }
+
+    /// See [`crate::assembly::ManagedAssembly::register_internal_call`]
+    pub fn register(&mut self, registration: &InternalCallRegistration) -> &mut Self {
+        self.insert(
+            registration.class_name,
+            registration.variable_name,
+            RegisteredInternalCall {
+                function: (registration.function)(),
+                signature: Some((registration.signature)()),
+                closure: None,
+                host: Some(InternalCallHostSlot::Static((registration.host)())),
+            },
+        )
+    }
+
+    /// See [`crate::assembly::ManagedAssembly::add_internal_call_unchecked`]
+    ///
+    /// ## Safety
+    /// Same as [`crate::assembly::ManagedAssembly::add_internal_call_unchecked`]
+    pub unsafe fn add_unchecked(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        fn_ptr: *const unsafe extern "system" fn() -> (),
+    ) -> &mut Self {
+        self.insert(
+            class_name,
+            variable_name,
+            RegisteredInternalCall {
+                function: fn_ptr as _,
+                signature: None,
+                closure: None,
+                host: None,
+            },
+        )
+    }
+
+    fn insert(
+        &mut self,
+        class_name: &str,
+        variable_name: &str,
+        call: RegisteredInternalCall,
+    ) -> &mut Self {
+        self.calls
+            .insert((class_name.to_string(), variable_name.to_string()), call);
+
+        self
+    }
+}
+
+/// A parameter type of an [`crate::internal_call`] function, converted from the FFI-safe type C# passes
+pub trait InternalCallArg {
+    type Native: FfiSafe;
+
+    fn from_native(native: Self::Native, host: &InternalCallHost) -> Self;
+}
+
+/// The return type of an [`crate::internal_call`] function, converted into the FFI-safe type C# expects
+pub trait InternalCallReturn {
+    type Native: FfiSafe;
+
+    fn into_native(self) -> Self::Native;
+}
+
+macro_rules! impl_internal_call_primitive {
+    ($($type:ty),*) => {
+        $(
+            impl InternalCallArg for $type {
+                type Native = $type;
+
+                fn from_native(native: Self::Native, _host: &InternalCallHost) -> Self {
+                    native
+                }
+            }
+
+            impl InternalCallReturn for $type {
+                type Native = $type;
+
+                fn into_native(self) -> Self::Native {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_internal_call_primitive!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64);
+
+impl<T: 'static> InternalCallArg for *mut T {
+    type Native = *mut T;
+
+    fn from_native(native: Self::Native, _host: &InternalCallHost) -> Self {
+        native
+    }
+}
+
+impl<T: 'static> InternalCallReturn for *mut T {
+    type Native = *mut T;
+
+    fn into_native(self) -> Self::Native {
+        self
+    }
+}
+
+impl InternalCallReturn for () {
+    type Native = ();
+
+    fn into_native(self) -> Self::Native {}
+}
+
+/// A C# `bool`, which is one byte that can be any value
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy)]
+pub struct NativeBool(u8);
+
+impl InternalCallArg for bool {
+    type Native = NativeBool;
+
+    fn from_native(native: Self::Native, _host: &InternalCallHost) -> Self {
+        native.0 != 0
+    }
+}
+
+impl InternalCallReturn for bool {
+    type Native = NativeBool;
+
+    fn into_native(self) -> Self::Native {
+        NativeBool(self as u8)
+    }
+}
+
+impl InternalCallArg for String {
+    type Native = CSharpNativeString;
+
+    fn from_native(native: Self::Native, _host: &InternalCallHost) -> Self {
+        if native.string.is_null() {
+            return String::new();
+        }
+
+        native.to_string()
+    }
+}
+
+/// Allocated with the allocator `Marshal.FreeCoTaskMem` uses, so C# owns the returned `NativeString` and has to dispose it
+impl InternalCallReturn for String {
+    type Native = CSharpNativeString;
+
+    fn into_native(self) -> Self::Native {
+        CSharpNativeString::new_co_task_mem(&self)
+    }
+}
+
+/// Coral's `NativeArray<T>`, the C# side of a slice parameter
+#[repr(C)]
+pub struct NativeArray<T> {
+    data: *const T,
+    _array_handle: *const std::ffi::c_void,
+    length: i32,
+    _is_disposed: Bool32,
+}
+
+impl<T: FfiSafe> InternalCallArg for &[T] {
+    type Native = NativeArray<T>;
+
+    // NOTE: The array is only guaranteed to be alive for the duration of the call, which is all the slice is used for
+    fn from_native(native: Self::Native, _host: &InternalCallHost) -> Self {
+        if native.data.is_null() || native.length <= 0 {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(native.data, native.length as usize) }
+    }
+}
+
+/// Passed as a `GCHandle` (`IntPtr`). The handle is owned by C#, so the object must not be destroyed.
+impl InternalCallArg for ManagedObject {
+    type Native = *mut std::ffi::c_void;
+
+    fn from_native(native: Self::Native, host: &InternalCallHost) -> Self {
+        let host = host
+            .get()
+            .expect("Internal call made before a HostInstance bound it");
+
+        let mut object = ManagedObject::uninit(&host);
+        object.handle = native;
+
+        object
+    }
+}
+
+/// Returned as its `GCHandle` (`IntPtr`), which C# takes ownership of
+impl InternalCallReturn for ManagedObject {
+    type Native = *mut std::ffi::c_void;
+
+    fn into_native(self) -> Self::Native {
+        self.handle
+    }
+}
+
+/// The return type of an [`crate::internal_call`] function that can fail. The shim takes the error parameter
+/// [`InternalCallSignature::of_fallible`] describes, `Err` is written to it and C# throws it as an `InternalCallFailedException`.
+pub trait InternalCallResult {
+    type Native: FfiSafe;
+
+    /// ## Safety
+    /// `error` must be null or valid for writes
+    unsafe fn into_native(self, error: *mut CSharpNativeString) -> Self::Native;
+}
+
+/// The value returned with an `Err` is never read, C# throws before it could be
+impl<T: InternalCallReturn + Default, E: Display> InternalCallResult for Result<T, E> {
+    type Native = T::Native;
+
+    unsafe fn into_native(self, error: *mut CSharpNativeString) -> Self::Native {
+        match self {
+            Ok(value) => value.into_native(),
+            Err(err) => {
+                if error.is_null() {
+                    log_message(format!("Internal call failed: {err}"), MessageLevel::Error);
+                } else {
+                    unsafe {
+                        error.write(CSharpNativeString::new_co_task_mem(&err.to_string()));
+                    }
+                }
+
+                T::default().into_native()
+            }
+        }
+    }
+}
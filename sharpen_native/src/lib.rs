@@ -1,8 +1,19 @@
 pub mod assembly;
+pub mod async_internal_call;
+pub mod compiler;
+pub mod context_info;
+pub mod declarations;
 pub mod host_instance;
+pub mod initializer;
+pub mod internal_call;
 pub mod message_level;
 pub mod meta_info;
+pub mod plugin_host;
+pub mod resolver;
+pub mod sandbox;
 pub mod string;
+pub mod verification;
+pub mod virtual_source;
 
 mod coral_managed_fns;
 pub mod from_csharp;
@@ -10,8 +21,14 @@ pub mod managed_object;
 mod managed_type;
 mod sharp_type;
 mod type_cache;
+mod type_index;
 
+pub use async_internal_call::{AsyncInternalClosure, InternalCallExecutor};
+pub use coral_managed_fns::AssemblyLoadStatus;
+pub use internal_call::{InternalCallFn, InternalClosure};
+pub use managed_type::FfiSafe;
 pub use sharp_type::TypeFns;
+pub use sharpen_macros::internal_call;
 pub use type_cache::TypeCacheError;
 
 #[repr(transparent)]
@@ -20,6 +37,12 @@ struct Bool32(pub(crate) std::ffi::c_ulong);
 type TypeId = std::ffi::c_long;
 type ManagedHandle = std::ffi::c_long;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The `AssemblyLoadContext` the object or type belongs to has been unloaded
+    ContextUnloaded,
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub enum TypeAccessibility {
@@ -32,7 +55,17 @@ pub enum TypeAccessibility {
 }
 
 #[allow(unused)]
+#[repr(C)]
 struct InternalCall {
     name: *const netcorehost::pdcstring::PdChar,
     native_function_ptr: *const std::ffi::c_void,
+    parameter_types: *const managed_type::ManagedType,
+    /// -1 if the signature is unknown, in which case C# doesn't validate it
+    parameter_count: i32,
+    return_type: managed_type::ManagedType,
+    /// Passed back by C# as the first argument of closures, null for plain functions
+    context: *const std::ffi::c_void,
+    /// The native function takes a `*mut CSharpNativeString` after its parameters, which it sets to the error if it fails
+    reports_errors: Bool32,
+    _padding: u32,
 }
@@ -1,13 +1,14 @@
 use std::sync::Arc;
 
 use crate::{
-    from_csharp::FromCSharp, host_instance::HostInstance, managed_type::GetManagedType,
-    sharp_type::Type, string::CSharpNativeString,
+    Error, assembly::ContextStamp, from_csharp::FromCSharp, host_instance::HostInstance,
+    managed_type::GetManagedType, sharp_type::Type, string::CSharpNativeString,
 };
 
 pub struct ManagedObject {
     pub(crate) handle: *mut std::ffi::c_void,
     pub(crate) r#type: Option<Arc<Type>>,
+    pub(crate) context: Option<ContextStamp>,
 
     host: HostInstance,
 }
@@ -17,25 +18,26 @@ impl ManagedObject {
         Self {
             handle: std::ptr::null_mut(),
             r#type: None,
+            context: None,
             host: host.clone(),
         }
     }
 
-    pub fn get_type(&mut self) -> Arc<Type> {
-        if self.r#type.is_none() {
-            let mut r#type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_object_type_id)(self.handle, &mut r#type.id as _);
+    pub fn get_type(&mut self) -> Result<Arc<Type>, Error> {
+        self.check_context()?;
 
-            let r#type = Arc::new(r#type);
-            self.host.type_cache().cache_type(r#type.clone());
-            self.r#type = Some(r#type);
+        if self.r#type.is_none() {
+            let mut type_id = -1;
+            (self.host.managed_functions().get_object_type_id)(self.handle, &mut type_id as _);
+            self.r#type = Some(Type::resolve(type_id, self.context.as_ref(), &self.host));
         }
 
-        self.r#type.clone().unwrap()
+        Ok(self.r#type.clone().unwrap())
     }
 
+    /// Frees the object's handle, does nothing if its context was already unloaded (which frees every handle)
     pub fn destroy(self) {
-        if self.handle.is_null() {
+        if self.handle.is_null() || self.check_context().is_err() {
             return;
         }
 
@@ -43,11 +45,24 @@ impl ManagedObject {
     }
 
     pub fn is_valid(&self) -> bool {
-        !self.handle.is_null() && self.r#type.is_some()
+        !self.handle.is_null() && self.r#type.is_some() && self.check_context().is_ok()
+    }
+
+    fn check_context(&self) -> Result<(), Error> {
+        match &self.context {
+            Some(context) if !context.is_alive() => Err(Error::ContextUnloaded),
+            _ => Ok(()),
+        }
     }
 
     // TODO: Type conversions
-    pub fn set_field_value<FieldType>(&self, name: &str, mut value: FieldType) {
+    pub fn set_field_value<FieldType>(
+        &self,
+        name: &str,
+        mut value: FieldType,
+    ) -> Result<(), Error> {
+        self.check_context()?;
+
         let mut field_name = CSharpNativeString::new(name);
 
         (self.host.managed_functions().set_field_value)(
@@ -57,9 +72,16 @@ impl ManagedObject {
         );
 
         CSharpNativeString::free(&mut field_name);
+
+        Ok(())
     }
 
-    pub fn get_field_value<CSharp, FieldType: FromCSharp<CSharp>>(&self, name: &str) -> FieldType {
+    pub fn get_field_value<CSharp, FieldType: FromCSharp<CSharp>>(
+        &self,
+        name: &str,
+    ) -> Result<FieldType, Error> {
+        self.check_context()?;
+
         let mut field_name = CSharpNativeString::new(name);
 
         let mut result: CSharp = unsafe { std::mem::zeroed() };
@@ -71,10 +93,16 @@ impl ManagedObject {
         );
         CSharpNativeString::free(&mut field_name);
 
-        FieldType::from_csharp(result)
+        Ok(FieldType::from_csharp(result))
     }
 
-    pub fn set_property_value<PropertyType>(&self, name: &str, mut value: PropertyType) {
+    pub fn set_property_value<PropertyType>(
+        &self,
+        name: &str,
+        mut value: PropertyType,
+    ) -> Result<(), Error> {
+        self.check_context()?;
+
         let mut property_name = CSharpNativeString::new(name);
 
         (self.host.managed_functions().set_property_value)(
@@ -84,12 +112,16 @@ impl ManagedObject {
         );
 
         CSharpNativeString::free(&mut property_name);
+
+        Ok(())
     }
 
     pub fn get_property_value<CSharp, PropertyType: FromCSharp<CSharp>>(
         &self,
         name: &str,
-    ) -> PropertyType {
+    ) -> Result<PropertyType, Error> {
+        self.check_context()?;
+
         let mut property_name = CSharpNativeString::new(name);
 
         let mut result: CSharp = unsafe { std::mem::zeroed() };
@@ -102,18 +134,21 @@ impl ManagedObject {
         );
         CSharpNativeString::free(&mut property_name);
 
-        PropertyType::from_csharp(result)
+        Ok(PropertyType::from_csharp(result))
     }
 }
 
 // TODO: Get/Set Field/Property-value
 // TODO: Handle cleanup for CSharpNativeString
+/// Returns [`Error::ContextUnloaded`] if the object's `AssemblyLoadContext` was unloaded
 pub trait ManagedObjectFns<Args> {
-    fn invoke_method<Ret>(&self, name: &str, args: Args) -> Ret;
+    fn invoke_method<Ret>(&self, name: &str, args: Args) -> Result<Ret, Error>;
 }
 
 impl ManagedObjectFns<()> for ManagedObject {
-    fn invoke_method<Ret>(&self, name: &str, _args: ()) -> Ret {
+    fn invoke_method<Ret>(&self, name: &str, _args: ()) -> Result<Ret, Error> {
+        self.check_context()?;
+
         let mut method_name = CSharpNativeString::new(name);
 
         let result = unsafe {
@@ -133,7 +168,7 @@ impl ManagedObjectFns<()> for ManagedObject {
 
         CSharpNativeString::free(&mut method_name);
 
-        result
+        Ok(result)
     }
 }
 
@@ -153,7 +188,9 @@ macro_rules! impl_type_fns {
 	($($idx:tt $arg:tt),+) => {
 		impl<$($arg: 'static,)+> ManagedObjectFns<($($arg,)+)> for ManagedObject
 		{
-			fn invoke_method<Ret>(&self, name: &str, mut args: ($($arg,)+)) -> Ret {
+			fn invoke_method<Ret>(&self, name: &str, mut args: ($($arg,)+)) -> Result<Ret, Error> {
+				self.check_context()?;
+
 				let mut method_name = CSharpNativeString::new(name);
 
 				let len = count_params!($($arg),+);
@@ -183,7 +220,7 @@ macro_rules! impl_type_fns {
 
                 CSharpNativeString::free(&mut method_name);
 
-				result
+				Ok(result)
 			}
 		}
 	};
@@ -1,3 +1,9 @@
+use crate::{
+    Bool32,
+    internal_call::{NativeArray, NativeBool},
+    string::CSharpNativeString,
+};
+
 // TODO: Is this the correct size?
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -17,10 +23,14 @@ pub enum ManagedType {
     Double,
 
     Bool,
+    /// Coral's 4 byte `Bool32`, unlike `Bool` which is a one byte C# `bool`
+    Bool32,
 
     String,
 
     Pointer,
+
+    Void,
 }
 
 pub trait GetManagedType {
@@ -56,7 +66,58 @@ impl<T: 'static> GetManagedType for T {
             id if id == std::any::TypeId::of::<f64>() => ManagedType::Double,
             id if id == std::any::TypeId::of::<bool>() => ManagedType::Bool,
             id if id == std::any::TypeId::of::<String>() => ManagedType::String,
+            id if id == std::any::TypeId::of::<CSharpNativeString>() => ManagedType::String,
+            id if id == std::any::TypeId::of::<NativeBool>() => ManagedType::Bool,
+            id if id == std::any::TypeId::of::<Bool32>() => ManagedType::Bool32,
+            id if id == std::any::TypeId::of::<()>() => ManagedType::Void,
             _ => ManagedType::Unknown,
         }
     }
 }
+
+/// A type that crosses an internal call as is, so it has the same layout as the C# type of the `delegate* unmanaged`
+/// parameter or return value it's bound to.
+///
+/// ## Safety
+/// Implementors must be `#[repr(C)]` or `#[repr(transparent)]`, and valid for every value C# can pass.
+/// `MANAGED_TYPE` defaults to `Unknown`, which only matches C# structs.
+pub unsafe trait FfiSafe: 'static {
+    const MANAGED_TYPE: ManagedType = ManagedType::Unknown;
+}
+
+macro_rules! impl_ffi_safe {
+    ($($type:ty => $managed_type:ident),*) => {
+        $(
+            unsafe impl FfiSafe for $type {
+                const MANAGED_TYPE: ManagedType = ManagedType::$managed_type;
+            }
+        )*
+    };
+}
+
+impl_ffi_safe!(
+    i8 => SByte,
+    u8 => Byte,
+    i16 => Short,
+    u16 => UShort,
+    i32 => Int,
+    u32 => UInt,
+    i64 => Long,
+    u64 => ULong,
+    f32 => Float,
+    f64 => Double,
+    NativeBool => Bool,
+    Bool32 => Bool32,
+    CSharpNativeString => String,
+    () => Void
+);
+
+unsafe impl<T: 'static> FfiSafe for *mut T {
+    const MANAGED_TYPE: ManagedType = ManagedType::Pointer;
+}
+
+unsafe impl<T: 'static> FfiSafe for *const T {
+    const MANAGED_TYPE: ManagedType = ManagedType::Pointer;
+}
+
+unsafe impl<T: FfiSafe> FfiSafe for NativeArray<T> {}
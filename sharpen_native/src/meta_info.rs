@@ -23,12 +23,9 @@ impl Attribute {
 
     pub fn get_type(&mut self) -> Arc<Type> {
         if self.r#type.is_none() {
-            let mut r#type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_attribute_type)(self.handle, &mut r#type.id as _);
-
-            let r#type = Arc::new(r#type);
-            self.host.type_cache().cache_type(r#type.clone());
-            self.r#type = Some(r#type);
+            let mut type_id = -1;
+            (self.host.managed_functions().get_attribute_type)(self.handle, &mut type_id as _);
+            self.r#type = Some(Type::resolve(type_id, None, &self.host));
         }
 
         self.r#type.clone().unwrap()
@@ -47,6 +44,11 @@ impl Attribute {
 
         Ret::from_csharp(result)
     }
+
+    /// Releases the attribute instance C# keeps alive for this handle
+    pub fn destroy(self) {
+        (self.host.managed_functions().destroy_attribute)(self.handle);
+    }
 }
 
 pub struct MethodInfo {
@@ -73,15 +75,12 @@ impl MethodInfo {
 
     pub fn get_return_type(&mut self) -> Arc<Type> {
         if self.return_type.is_none() {
-            let mut return_type = Type::uninit(&self.host);
+            let mut return_type_id = -1;
             (self.host.managed_functions().get_method_info_return_type)(
                 self.handle,
-                &mut return_type.id,
+                &mut return_type_id,
             );
-
-            let return_type = Arc::new(return_type);
-            self.host.type_cache().cache_type(return_type.clone());
-            self.return_type = Some(return_type);
+            self.return_type = Some(Type::resolve(return_type_id, None, &self.host));
         }
 
         self.return_type.clone().unwrap()
@@ -114,11 +113,7 @@ impl MethodInfo {
 
             self.parameter_types = parameter_type_ids
                 .iter()
-                .map(|id| {
-                    let r#type = Arc::new(Type::from_id(*id, &self.host));
-                    self.host.type_cache().cache_type(r#type.clone());
-                    r#type
-                })
+                .map(|id| Type::resolve(*id, None, &self.host))
                 .collect();
         }
 
@@ -176,12 +171,9 @@ impl FieldInfo {
 
     pub fn get_type(&mut self) -> Arc<Type> {
         if self.r#type.is_none() {
-            let mut r#type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_field_info_type)(self.handle, &mut r#type.id);
-
-            let r#type = Arc::new(r#type);
-            self.host.type_cache().cache_type(r#type.clone());
-            self.r#type = Some(r#type);
+            let mut type_id = -1;
+            (self.host.managed_functions().get_field_info_type)(self.handle, &mut type_id);
+            self.r#type = Some(Type::resolve(type_id, None, &self.host));
         }
 
         self.r#type.clone().unwrap()
@@ -238,12 +230,9 @@ impl PropertyInfo {
 
     pub fn get_type(&mut self) -> Arc<Type> {
         if self.r#type.is_none() {
-            let mut r#type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_property_info_type)(self.handle, &mut r#type.id);
-
-            let r#type = Arc::new(r#type);
-            self.host.type_cache().cache_type(r#type.clone());
-            self.r#type = Some(r#type);
+            let mut type_id = -1;
+            (self.host.managed_functions().get_property_info_type)(self.handle, &mut type_id);
+            self.r#type = Some(Type::resolve(type_id, None, &self.host));
         }
 
         self.r#type.clone().unwrap()
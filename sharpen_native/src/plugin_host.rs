@@ -0,0 +1,405 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    AssemblyLoadStatus, TypeFns,
+    assembly::{AssemblyLoadContext, AssemblyLoadError, ManagedAssembly},
+    host_instance::HostInstance,
+    managed_object::ManagedObject,
+};
+
+#[derive(Clone)]
+pub struct PluginHostSettings {
+    /// Fully qualified name of the attribute that marks plugin entry types (e.g MyEngine.PluginEntryAttribute).
+    /// Every type with this attribute is instantiated through its parameterless constructor.
+    pub entry_attribute: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginStatus {
+    Loaded,
+    /// The assembly loaded, but none of its types have the entry attribute
+    NoEntryTypes,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub enum PluginLoadError {
+    ManifestUnreadable(PathBuf),
+    AssemblyLoadError(AssemblyLoadError),
+    /// The assembly couldn't be loaded (e.g it isn't a managed assembly, or a dependency is missing)
+    AssemblyLoadFailed(AssemblyLoadStatus),
+    /// Creating an instance of the entry type (by full name) failed
+    EntryTypeNotInstantiable(String),
+}
+
+pub struct Plugin {
+    name: String,
+    version: String,
+    path: PathBuf,
+    status: PluginStatus,
+    errors: Vec<PluginLoadError>,
+
+    assembly: Option<Arc<ManagedAssembly>>,
+    entry_objects: Vec<ManagedObject>,
+    // NOTE: Has to be declared last, so it is unloaded after everything referencing it
+    _context: Option<AssemblyLoadContext>,
+}
+
+impl Plugin {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn status(&self) -> PluginStatus {
+        self.status
+    }
+
+    pub fn errors(&self) -> &[PluginLoadError] {
+        &self.errors
+    }
+
+    pub fn assembly(&self) -> Option<&Arc<ManagedAssembly>> {
+        self.assembly.as_ref()
+    }
+
+    /// The instances created for every entry type in the plugin
+    pub fn entry_objects(&self) -> &[ManagedObject] {
+        &self.entry_objects
+    }
+
+    fn failed(path: &Path, error: PluginLoadError) -> Self {
+        Self {
+            name: plugin_name(path),
+            version: String::new(),
+            path: path.to_path_buf(),
+            status: PluginStatus::Failed,
+            errors: vec![error],
+            assembly: None,
+            entry_objects: vec![],
+            _context: None,
+        }
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        for object in self.entry_objects.drain(..) {
+            object.destroy();
+        }
+    }
+}
+
+/// Loads every plugin into its own [`AssemblyLoadContext`] and instantiates its entry types
+pub struct PluginHost {
+    host: HostInstance,
+    settings: PluginHostSettings,
+    plugins: Vec<Plugin>,
+}
+
+impl PluginHost {
+    pub fn new(host: &HostInstance, settings: PluginHostSettings) -> Self {
+        Self {
+            host: host.clone(),
+            settings,
+            plugins: vec![],
+        }
+    }
+
+    /// Loads every `*.dll` directly inside `directory` as a plugin, except the package assemblies a plugin's `.deps.json`
+    /// depends on. Assemblies of project references are still loaded, since they can be plugins themselves.
+    pub fn load_directory(&mut self, directory: &Path) -> std::io::Result<()> {
+        let paths = std::fs::read_dir(directory)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+
+        let mut dependencies = HashSet::new();
+        for path in &paths {
+            let is_dependency_manifest = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.ends_with(".deps.json"));
+            if !is_dependency_manifest {
+                continue;
+            }
+
+            let package_assemblies = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|manifest| manifest_package_assemblies(&manifest));
+            match package_assemblies {
+                Some(package_assemblies) => dependencies.extend(package_assemblies),
+                None => self.plugins.push(Plugin::failed(
+                    path,
+                    PluginLoadError::ManifestUnreadable(path.clone()),
+                )),
+            }
+        }
+
+        let mut assembly_paths = paths
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|extension| extension == "dll"))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_none_or(|name| !dependencies.contains(name))
+            })
+            .collect::<Vec<_>>();
+        assembly_paths.sort();
+
+        for assembly_path in assembly_paths {
+            self.load_plugin(&assembly_path);
+        }
+
+        Ok(())
+    }
+
+    /// A manifest is a text file with one plugin assembly path per line, relative to the manifest itself.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load_manifests(&mut self, manifests: &[PathBuf]) {
+        for manifest in manifests {
+            let Ok(contents) = std::fs::read_to_string(manifest) else {
+                self.plugins.push(Plugin::failed(
+                    manifest,
+                    PluginLoadError::ManifestUnreadable(manifest.clone()),
+                ));
+                continue;
+            };
+
+            let manifest_directory = manifest.parent().unwrap_or(Path::new(""));
+            for line in contents.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                self.load_plugin(&manifest_directory.join(line));
+            }
+        }
+    }
+
+    pub fn load_plugin(&mut self, assembly_path: &Path) -> &Plugin {
+        let plugin = self.create_plugin(assembly_path);
+        self.plugins.push(plugin);
+
+        self.plugins.last().unwrap()
+    }
+
+    pub fn plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    /// Destroys the entry objects and unloads the context of every plugin
+    pub fn unload_all(&mut self) {
+        self.plugins.clear();
+    }
+
+    fn create_plugin(&self, assembly_path: &Path) -> Plugin {
+        let name = plugin_name(assembly_path);
+
+        let mut context = self
+            .host
+            .create_assembly_load_context(&format!("Plugin:{name}"));
+        if let Some(directory) = assembly_path.parent() {
+            context.add_probing_directory(directory);
+        }
+        if assembly_path.with_extension("deps.json").exists() {
+            context.use_dependency_manifest(assembly_path);
+        }
+
+        let assembly = match context.load_assembly(assembly_path) {
+            Ok(assembly) => assembly,
+            Err(err) => {
+                return Plugin::failed(assembly_path, PluginLoadError::AssemblyLoadError(err));
+            }
+        };
+        if assembly.load_status() != AssemblyLoadStatus::Success {
+            return Plugin::failed(
+                assembly_path,
+                PluginLoadError::AssemblyLoadFailed(assembly.load_status()),
+            );
+        }
+
+        let mut entry_objects = vec![];
+        let mut errors = vec![];
+
+        for r#type in &assembly.types {
+            let Ok(mut attributes) = r#type.get_attributes() else {
+                continue;
+            };
+            let is_entry_type = attributes.iter_mut().any(|attribute| {
+                attribute
+                    .get_type()
+                    .get_full_name()
+                    .is_ok_and(|name| name == self.settings.entry_attribute)
+            });
+            for attribute in attributes {
+                attribute.destroy();
+            }
+
+            if !is_entry_type {
+                continue;
+            }
+
+            match r#type.create_instance(()) {
+                Ok(object) if object.is_valid() => entry_objects.push(object),
+                _ => {
+                    errors.push(PluginLoadError::EntryTypeNotInstantiable(
+                        r#type.get_full_name().unwrap_or_default(),
+                    ));
+                }
+            }
+        }
+
+        let status = if !errors.is_empty() {
+            PluginStatus::Failed
+        } else if entry_objects.is_empty() {
+            PluginStatus::NoEntryTypes
+        } else {
+            PluginStatus::Loaded
+        };
+
+        Plugin {
+            name: assembly.name().to_string(),
+            version: assembly.version(),
+            path: assembly_path.to_path_buf(),
+            status,
+            errors,
+            assembly: Some(assembly),
+            entry_objects,
+            _context: Some(context),
+        }
+    }
+}
+
+fn plugin_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The file names of the runtime assemblies of every package (or plain assembly reference) in a `.deps.json`,
+/// `None` if it isn't a valid one. Project references are left out, the plugin itself is one of them.
+fn manifest_package_assemblies(manifest: &str) -> Option<Vec<String>> {
+    let manifest = serde_json::from_str::<serde_json::Value>(manifest).ok()?;
+
+    let target_name = manifest.pointer("/runtimeTarget/name")?.as_str()?;
+    let target = manifest.get("targets")?.get(target_name)?.as_object()?;
+    let libraries = manifest.get("libraries")?.as_object()?;
+
+    let file_names = target
+        .iter()
+        .filter(|(library, _)| {
+            libraries
+                .get(*library)
+                .and_then(|library| library.get("type"))
+                .and_then(serde_json::Value::as_str)
+                != Some("project")
+        })
+        .filter_map(|(_, library)| library.get("runtime")?.as_object())
+        .flat_map(|runtime| runtime.keys())
+        .filter_map(|path| path.rsplit('/').next())
+        .filter(|file_name| file_name.ends_with(".dll"))
+        .map(str::to_string)
+        .collect();
+
+    Some(file_names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by `dotnet build` for a plugin with a package and a project reference
+    const DEPS_JSON: &str = r#"{
+  "runtimeTarget": {
+    "name": ".NETCoreApp,Version=v8.0",
+    "signature": ""
+  },
+  "compilationOptions": {},
+  "targets": {
+    ".NETCoreApp,Version=v8.0": {
+      "Example.Plugin/1.0.0": {
+        "dependencies": {
+          "Example.Shared": "1.0.0",
+          "Newtonsoft.Json": "13.0.3"
+        },
+        "runtime": {
+          "Example.Plugin.dll": {}
+        }
+      },
+      "Newtonsoft.Json/13.0.3": {
+        "runtime": {
+          "lib/net6.0/Newtonsoft.Json.dll": {
+            "assemblyVersion": "13.0.0.0",
+            "fileVersion": "13.0.3.27908"
+          }
+        }
+      },
+      "Example.Shared/1.0.0": {
+        "runtime": {
+          "Example.Shared.dll": {
+            "assemblyVersion": "1.0.0.0",
+            "fileVersion": "1.0.0.0"
+          }
+        }
+      }
+    }
+  },
+  "libraries": {
+    "Example.Plugin/1.0.0": {
+      "type": "project",
+      "serviceable": false,
+      "sha512": ""
+    },
+    "Newtonsoft.Json/13.0.3": {
+      "type": "package",
+      "serviceable": true,
+      "sha512": "sha512-HrC5BXdl00IP9zeV+0Z848QWPAoCr9P3bDEZguI+gkLcBKAOxix/tLEAAHC+UvDNPv4a2d18lOReHMOagPa+zQ==",
+      "path": "newtonsoft.json/13.0.3",
+      "hashPath": "newtonsoft.json.13.0.3.nupkg.sha512"
+    },
+    "Example.Shared/1.0.0": {
+      "type": "project",
+      "serviceable": false,
+      "sha512": ""
+    }
+  }
+}"#;
+
+    #[test]
+    fn package_assemblies_are_dependencies() {
+        assert_eq!(
+            manifest_package_assemblies(DEPS_JSON),
+            Some(vec!["Newtonsoft.Json.dll".to_string()])
+        );
+    }
+
+    #[test]
+    fn runtime_keys_outside_the_target_are_ignored() {
+        let manifest = DEPS_JSON.replace(
+            r#""signature": """#,
+            r#""signature": "", "runtime": { "Example.Other.dll": {} }"#,
+        );
+
+        assert_eq!(
+            manifest_package_assemblies(&manifest),
+            Some(vec!["Newtonsoft.Json.dll".to_string()])
+        );
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        assert_eq!(manifest_package_assemblies("{ \"targets\": "), None);
+        assert_eq!(manifest_package_assemblies("{}"), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    ffi::c_void,
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::{
+    Bool32,
+    host_instance::log_message,
+    message_level::MessageLevel,
+    string::{CSharpNativeString, ScopedCSharpNativeString},
+    virtual_source::{VirtualAssemblySource, find_assembly},
+};
+
+/// The name of an assembly the runtime failed to find on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyName {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+pub enum AssemblyResolution {
+    /// Load the assembly from a file on disk
+    Path(PathBuf),
+    /// Load the assembly (and optionally its portable PDB) from memory
+    Bytes {
+        assembly: Vec<u8>,
+        symbols: Option<Vec<u8>>,
+    },
+}
+
+pub type AssemblyResolverFn =
+    Box<dyn Fn(&AssemblyName) -> Option<AssemblyResolution> + Send + Sync>;
+pub type UnmanagedResolverFn = Box<dyn Fn(&str) -> Option<PathBuf> + Send + Sync>;
+
+#[repr(C)]
+pub(crate) struct NativeAssemblyResolution {
+    path: CSharpNativeString,
+    assembly_data: *const u8,
+    assembly_data_length: i64,
+    symbols_data: *const u8,
+    symbols_data_length: i64,
+}
+
+pub(crate) type AssemblyResolverCallbackFn = extern "system" fn(
+    *mut c_void,
+    CSharpNativeString,
+    CSharpNativeString,
+    *mut NativeAssemblyResolution,
+) -> Bool32;
+pub(crate) type UnmanagedResolverCallbackFn =
+    extern "system" fn(*mut c_void, CSharpNativeString, *mut CSharpNativeString) -> Bool32;
+
+/// Everything C# needs to call back into rust when an `AssemblyLoadContext` can't resolve a dependency.
+/// A pointer to this is handed to C# as user data, so it has to stay at a stable address (boxed) for
+/// as long as the context is alive.
+#[derive(Default)]
+pub(crate) struct ResolverState {
+    pub(crate) assembly_resolver: Option<AssemblyResolverFn>,
+    pub(crate) unmanaged_resolver: Option<UnmanagedResolverFn>,
+    pub(crate) mounts: Vec<Arc<dyn VirtualAssemblySource>>,
+    // NOTE: C# copies the data out before the next resolve, so we only have to keep the last result alive
+    last_resolution: Mutex<Option<AssemblyResolution>>,
+    last_path: Mutex<Option<ScopedCSharpNativeString>>,
+}
+
+impl ResolverState {
+    /// Mounted sources are searched first (in the order they were mounted), then the assembly resolver
+    pub(crate) fn resolve_assembly(&self, name: &AssemblyName) -> Option<AssemblyResolution> {
+        let mounted = self
+            .mounts
+            .iter()
+            .find_map(|source| find_assembly(source.as_ref(), &name.name));
+        if let Some((assembly, symbols)) = mounted {
+            return Some(AssemblyResolution::Bytes { assembly, symbols });
+        }
+
+        self.assembly_resolver
+            .as_ref()
+            .and_then(|resolver| resolver(name))
+    }
+
+    pub(crate) fn resolve_unmanaged(&self, name: &str) -> Option<PathBuf> {
+        self.unmanaged_resolver
+            .as_ref()
+            .and_then(|resolver| resolver(name))
+    }
+}
+
+fn optional_string(string: &CSharpNativeString) -> Option<String> {
+    if string.string.is_null() {
+        None
+    } else {
+        Some(string.to_string())
+    }
+}
+
+/// Runs a callback C# made, a panic can't unwind into C# so it's logged and reported as not resolved instead
+fn catch_resolver_panic(resolve: impl FnOnce() -> bool) -> Bool32 {
+    std::panic::catch_unwind(AssertUnwindSafe(resolve))
+        .unwrap_or_else(|_| {
+            log_message(
+                "A resolver panicked, the assembly is treated as not found".to_string(),
+                MessageLevel::Error,
+            );
+            false
+        })
+        .into()
+}
+
+pub(crate) extern "system" fn resolve_assembly_callback(
+    user_data: *mut c_void,
+    name: CSharpNativeString,
+    version: CSharpNativeString,
+    out_resolution: *mut NativeAssemblyResolution,
+) -> Bool32 {
+    catch_resolver_panic(|| resolve_assembly(user_data, name, version, out_resolution))
+}
+
+fn resolve_assembly(
+    user_data: *mut c_void,
+    name: CSharpNativeString,
+    version: CSharpNativeString,
+    out_resolution: *mut NativeAssemblyResolution,
+) -> bool {
+    let state = unsafe { &*(user_data as *const ResolverState) };
+
+    let Some(name) = optional_string(&name) else {
+        return false;
+    };
+    let name = AssemblyName {
+        name,
+        version: optional_string(&version),
+    };
+
+    let Some(resolution) = state.resolve_assembly(&name) else {
+        return false;
+    };
+
+    // NOTE: They only hold the last result, so they're still usable after a resolver panicked while holding them
+    let mut last_path = state
+        .last_path
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let mut last_resolution = state
+        .last_resolution
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let native = match &resolution {
+        AssemblyResolution::Path(path) => {
+            let path = ScopedCSharpNativeString::from_str(&path.to_string_lossy());
+            let native = NativeAssemblyResolution {
+                path: path.inner(),
+                assembly_data: std::ptr::null(),
+                assembly_data_length: 0,
+                symbols_data: std::ptr::null(),
+                symbols_data_length: 0,
+            };
+            *last_path = Some(path);
+
+            native
+        }
+        AssemblyResolution::Bytes { assembly, symbols } => NativeAssemblyResolution {
+            path: CSharpNativeString::null(),
+            assembly_data: assembly.as_ptr(),
+            assembly_data_length: assembly.len() as i64,
+            symbols_data: symbols
+                .as_ref()
+                .map_or(std::ptr::null(), |symbols| symbols.as_ptr()),
+            symbols_data_length: symbols.as_ref().map_or(0, |symbols| symbols.len() as i64),
+        },
+    };
+
+    unsafe {
+        out_resolution.write(native);
+    }
+    *last_resolution = Some(resolution);
+
+    true
+}
+
+pub(crate) extern "system" fn resolve_unmanaged_callback(
+    user_data: *mut c_void,
+    name: CSharpNativeString,
+    out_path: *mut CSharpNativeString,
+) -> Bool32 {
+    catch_resolver_panic(|| resolve_unmanaged(user_data, name, out_path))
+}
+
+fn resolve_unmanaged(
+    user_data: *mut c_void,
+    name: CSharpNativeString,
+    out_path: *mut CSharpNativeString,
+) -> bool {
+    let state = unsafe { &*(user_data as *const ResolverState) };
+
+    let Some(path) = optional_string(&name).and_then(|name| state.resolve_unmanaged(&name)) else {
+        return false;
+    };
+
+    let path = ScopedCSharpNativeString::from_str(&path.to_string_lossy());
+    unsafe {
+        out_path.write(path.inner());
+    }
+    *state
+        .last_path
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(path);
+
+    true
+}
@@ -0,0 +1,65 @@
+use crate::{host_instance::HostInstance, string::CSharpNativeString};
+
+/// Restricts which APIs the assemblies of an [`crate::assembly::AssemblyLoadContext`] may reference. Every assembly
+/// (dependencies included) has its metadata and IL scanned before it's loaded.
+///
+/// Entries are prefixes of a namespace, type or member name, e.g `System.IO`, `System.Diagnostics.Process`
+/// or `System.Environment.Exit`. Nested types are separated with `+`.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxPolicy {
+    pub denied: Vec<String>,
+    /// Exceptions to `denied` (e.g allow `System.IO.Path` while denying `System.IO`)
+    pub allowed: Vec<String>,
+    /// Reject assemblies that declare `DllImport` methods
+    pub deny_pinvoke: bool,
+}
+
+/// A forbidden reference found in an assembly
+#[derive(Debug, Clone)]
+pub struct SandboxFinding {
+    /// Full name of the type that contains the reference
+    pub type_name: String,
+    /// The method that contains the reference, `None` if the type itself does (e.g as its base type)
+    pub method_name: Option<String>,
+    /// The forbidden namespace, type or member that was referenced (or the IL opcode that couldn't be decoded,
+    /// which rejects the method since the rest of it can't be checked)
+    pub reference: String,
+}
+
+#[repr(C)]
+pub(crate) struct NativeSandboxFinding {
+    type_name: CSharpNativeString,
+    method_name: CSharpNativeString,
+    reference: CSharpNativeString,
+}
+
+pub(crate) fn last_sandbox_findings(host: &HostInstance) -> Vec<SandboxFinding> {
+    let get_last_sandbox_findings = &host.managed_functions().get_last_sandbox_findings;
+
+    let mut finding_count = 0i32;
+    get_last_sandbox_findings(std::ptr::null_mut(), &mut finding_count);
+
+    let mut findings = Vec::<NativeSandboxFinding>::with_capacity(finding_count as usize);
+    get_last_sandbox_findings(findings.as_mut_ptr(), &mut finding_count);
+    unsafe {
+        findings.set_len(finding_count as usize);
+    }
+
+    let result = findings
+        .iter()
+        .map(|finding| SandboxFinding {
+            type_name: finding.type_name.to_string(),
+            method_name: (!finding.method_name.string.is_null())
+                .then(|| finding.method_name.to_string()),
+            reference: finding.reference.to_string(),
+        })
+        .collect();
+
+    for finding in &mut findings {
+        CSharpNativeString::free(&mut finding.type_name);
+        CSharpNativeString::free(&mut finding.method_name);
+        CSharpNativeString::free(&mut finding.reference);
+    }
+
+    result
+}
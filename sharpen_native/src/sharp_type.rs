@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use crate::{
-    TypeId,
+    Error, TypeId,
+    assembly::ContextStamp,
     host_instance::HostInstance,
     managed_object::ManagedObject,
     managed_type::{GetManagedType, ManagedType},
@@ -13,6 +14,8 @@ pub struct Type {
     pub(crate) id: TypeId,
     base_type: Option<Arc<Type>>,
     element_type: Option<Arc<Type>>,
+    /// `None` for types that aren't owned by an unloadable context (e.g framework or shared types)
+    pub(crate) context: Option<ContextStamp>,
 
     host: HostInstance,
 }
@@ -23,6 +26,7 @@ impl Type {
             id: -1,
             base_type: None,
             element_type: None,
+            context: None,
             host: host.clone(),
         }
     }
@@ -32,51 +36,75 @@ impl Type {
             id,
             base_type: None,
             element_type: None,
+            context: None,
             host: host.clone(),
         }
     }
 
     // TODO: Make these functions safer? return rust types instead of C# types? and probably wrap in Result in case the managed function returns an invalid value?
-    pub fn get_full_name(&self) -> String {
+    pub fn get_full_name(&self) -> Result<String, Error> {
+        self.check_context()?;
+
         // TODO: Figure out if this leaks memory? does the gc expect us to clean the string up?
         let cs = (self.host.managed_functions().get_full_type_name)(self.id);
 
-        cs.to_string()
+        Ok(cs.to_string())
     }
 
-    pub fn get_assembly_qualified_name(&self) -> String {
+    pub fn get_assembly_qualified_name(&self) -> Result<String, Error> {
+        self.check_context()?;
+
         let cs = (self.host.managed_functions().get_assembly_qualified_name)(self.id);
 
-        cs.to_string()
+        Ok(cs.to_string())
     }
 
-    pub fn get_base_type(&mut self) -> &Type {
+    pub fn get_base_type(&mut self) -> Result<&Type, Error> {
+        self.check_context()?;
+
         if self.base_type.is_none() {
-            let mut base_type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_base_type)(self.id, &mut base_type.id);
-            self.base_type = Some(Arc::new(base_type));
+            let mut base_type_id = -1;
+            (self.host.managed_functions().get_base_type)(self.id, &mut base_type_id);
+            self.base_type = Some(Type::resolve(
+                base_type_id,
+                self.context.as_ref(),
+                &self.host,
+            ));
         }
 
-        self.base_type.as_ref().unwrap()
+        Ok(self.base_type.as_ref().unwrap())
     }
 
-    pub fn get_size(&self) -> i32 {
-        (self.host.managed_functions().get_type_size)(self.id)
+    pub fn get_size(&self) -> Result<i32, Error> {
+        self.check_context()?;
+
+        Ok((self.host.managed_functions().get_type_size)(self.id))
     }
 
-    pub fn is_subclass_of(&self, other: &Self) -> bool {
-        (self.host.managed_functions().is_type_subclass_of)(self.id, other.id).into()
+    pub fn is_subclass_of(&self, other: &Self) -> Result<bool, Error> {
+        self.check_context()?;
+        other.check_context()?;
+
+        Ok((self.host.managed_functions().is_type_subclass_of)(self.id, other.id).into())
     }
 
-    pub fn is_assignable_to(&self, other: &Self) -> bool {
-        (self.host.managed_functions().is_type_assignable_to)(self.id, other.id).into()
+    pub fn is_assignable_to(&self, other: &Self) -> Result<bool, Error> {
+        self.check_context()?;
+        other.check_context()?;
+
+        Ok((self.host.managed_functions().is_type_assignable_to)(self.id, other.id).into())
     }
 
-    pub fn is_assignable_from(&self, other: &Self) -> bool {
-        (self.host.managed_functions().is_type_assignable_from)(self.id, other.id).into()
+    pub fn is_assignable_from(&self, other: &Self) -> Result<bool, Error> {
+        self.check_context()?;
+        other.check_context()?;
+
+        Ok((self.host.managed_functions().is_type_assignable_from)(self.id, other.id).into())
     }
 
-    pub fn get_methods(&self) -> Vec<MethodInfo> {
+    pub fn get_methods(&self) -> Result<Vec<MethodInfo>, Error> {
+        self.check_context()?;
+
         let mut method_count = 0i32;
         (self.host.managed_functions().get_type_methods)(
             self.id,
@@ -94,13 +122,15 @@ impl Type {
             handles.set_len(method_count as usize);
         }
 
-        handles
+        Ok(handles
             .iter()
             .map(|handle| MethodInfo::from_handle(*handle, &self.host))
-            .collect()
+            .collect())
     }
 
-    pub fn get_fields(&self) -> Vec<FieldInfo> {
+    pub fn get_fields(&self) -> Result<Vec<FieldInfo>, Error> {
+        self.check_context()?;
+
         let mut field_count = 0i32;
         (self.host.managed_functions().get_type_fields)(
             self.id,
@@ -118,13 +148,15 @@ impl Type {
             handles.set_len(field_count as usize);
         }
 
-        handles
+        Ok(handles
             .iter()
             .map(|handle| FieldInfo::from_handle(*handle, &self.host))
-            .collect()
+            .collect())
     }
 
-    pub fn get_properties(&self) -> Vec<PropertyInfo> {
+    pub fn get_properties(&self) -> Result<Vec<PropertyInfo>, Error> {
+        self.check_context()?;
+
         let mut property_count = 0i32;
         (self.host.managed_functions().get_type_properties)(
             self.id,
@@ -142,17 +174,22 @@ impl Type {
             handles.set_len(property_count as usize);
         }
 
-        handles
+        Ok(handles
             .iter()
             .map(|handle| PropertyInfo::from_handle(*handle, &self.host))
-            .collect()
+            .collect())
     }
 
-    pub fn has_attribute(&self, attribute_type: &Type) -> bool {
-        (self.host.managed_functions().has_type_attribute)(self.id, attribute_type.id).into()
+    pub fn has_attribute(&self, attribute_type: &Type) -> Result<bool, Error> {
+        self.check_context()?;
+        attribute_type.check_context()?;
+
+        Ok((self.host.managed_functions().has_type_attribute)(self.id, attribute_type.id).into())
     }
 
-    pub fn get_attributes(&self) -> Vec<Attribute> {
+    pub fn get_attributes(&self) -> Result<Vec<Attribute>, Error> {
+        self.check_context()?;
+
         let mut attribute_count = 0i32;
         (self.host.managed_functions().get_type_attributes)(
             self.id,
@@ -170,31 +207,40 @@ impl Type {
             attribute_handles.set_len(attribute_count as usize);
         }
 
-        attribute_handles
+        Ok(attribute_handles
             .iter()
             .map(|handle| Attribute::from_handle(*handle, &self.host))
-            .collect()
+            .collect())
     }
 
-    pub fn get_managed_type(&self) -> ManagedType {
-        (self.host.managed_functions().get_type_managed_type)(self.id)
+    pub fn get_managed_type(&self) -> Result<ManagedType, Error> {
+        self.check_context()?;
+
+        Ok((self.host.managed_functions().get_type_managed_type)(
+            self.id,
+        ))
     }
 
-    pub fn is_sz_array(&self) -> bool {
-        (self.host.managed_functions().is_type_sz_array)(self.id).into()
+    pub fn is_sz_array(&self) -> Result<bool, Error> {
+        self.check_context()?;
+
+        Ok((self.host.managed_functions().is_type_sz_array)(self.id).into())
     }
 
-    pub fn get_element_type(&mut self) -> Arc<Type> {
-        if self.element_type.is_none() {
-            let mut element_type = Type::uninit(&self.host);
-            (self.host.managed_functions().get_element_type)(self.id, &mut element_type.id as _);
+    pub fn get_element_type(&mut self) -> Result<Arc<Type>, Error> {
+        self.check_context()?;
 
-            let element_type = Arc::new(element_type);
-            self.host.type_cache().cache_type(element_type.clone());
-            self.element_type = Some(element_type);
+        if self.element_type.is_none() {
+            let mut element_type_id = -1;
+            (self.host.managed_functions().get_element_type)(self.id, &mut element_type_id as _);
+            self.element_type = Some(Type::resolve(
+                element_type_id,
+                self.context.as_ref(),
+                &self.host,
+            ));
         }
 
-        self.element_type.clone().unwrap()
+        Ok(self.element_type.clone().unwrap())
     }
 
     pub fn get_type_id(&self) -> TypeId {
@@ -202,6 +248,35 @@ impl Type {
     }
 }
 
+impl Type {
+    /// The cached type with the id `id`, which keeps the context it was loaded with. Types that aren't cached yet
+    /// are owned by `context` and cached from now on.
+    pub(crate) fn resolve(
+        id: TypeId,
+        context: Option<&ContextStamp>,
+        host: &HostInstance,
+    ) -> Arc<Type> {
+        let mut type_cache = host.type_cache();
+        if let Ok(r#type) = type_cache.get_type_by_id(id) {
+            return r#type;
+        }
+
+        let mut r#type = Type::from_id(id, host);
+        r#type.context = context.cloned();
+
+        let r#type = Arc::new(r#type);
+        type_cache.cache_type(r#type.clone());
+        r#type
+    }
+
+    pub(crate) fn check_context(&self) -> Result<(), Error> {
+        match &self.context {
+            Some(context) if !context.is_alive() => Err(Error::ContextUnloaded),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl PartialEq for Type {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -209,13 +284,17 @@ impl PartialEq for Type {
 }
 impl Eq for Type {}
 
+/// Both return [`Error::ContextUnloaded`] if the type's `AssemblyLoadContext` was unloaded, like every other method of
+/// [`Type`] that calls into C#
 pub trait TypeFns<Args> {
-    fn create_instance(&self, args: Args) -> ManagedObject;
-    fn invoke_static_method<Ret>(&self, name: &str, args: Args) -> Ret;
+    fn create_instance(&self, args: Args) -> Result<ManagedObject, Error>;
+    fn invoke_static_method<Ret>(&self, name: &str, args: Args) -> Result<Ret, Error>;
 }
 
 impl TypeFns<()> for Type {
-    fn create_instance(&self, _args: ()) -> ManagedObject {
+    fn create_instance(&self, _args: ()) -> Result<ManagedObject, Error> {
+        self.check_context()?;
+
         let mut object = ManagedObject::uninit(&self.host);
         object.handle = (self.host.managed_functions().create_object)(
             self.id,
@@ -226,11 +305,14 @@ impl TypeFns<()> for Type {
         );
         // TODO: Is this the best way to do this?
         object.r#type = Some(self.host.type_cache().get_type_by_id(self.id).unwrap());
+        object.context = self.context.clone();
 
-        object
+        Ok(object)
     }
 
-    fn invoke_static_method<Ret>(&self, name: &str, _args: ()) -> Ret {
+    fn invoke_static_method<Ret>(&self, name: &str, _args: ()) -> Result<Ret, Error> {
+        self.check_context()?;
+
         let mut method_name = CSharpNativeString::new(name);
 
         let result = unsafe {
@@ -250,7 +332,7 @@ impl TypeFns<()> for Type {
 
         CSharpNativeString::free(&mut method_name);
 
-        result
+        Ok(result)
     }
 }
 
@@ -271,7 +353,9 @@ macro_rules! impl_type_fns {
 	($($idx:tt $arg:tt),+) => {
 		impl<$($arg: 'static,)+> TypeFns<($($arg,)+)> for Type
 		{
-            fn create_instance(&self, mut args: ($($arg,)+)) -> ManagedObject {
+            fn create_instance(&self, mut args: ($($arg,)+)) -> Result<ManagedObject, Error> {
+                self.check_context()?;
+
                 let mut object = ManagedObject::uninit(&self.host);
 
 				let len = count_params!($($arg),+);
@@ -293,11 +377,14 @@ macro_rules! impl_type_fns {
                 );
                 // TODO: Is this the best way to do this?
                 object.r#type = Some(self.host.type_cache().get_type_by_id(self.id).unwrap());
+                object.context = self.context.clone();
 
-                object
+                Ok(object)
             }
 
-			fn invoke_static_method<Ret>(&self, name: &str, mut args: ($($arg,)+)) -> Ret {
+			fn invoke_static_method<Ret>(&self, name: &str, mut args: ($($arg,)+)) -> Result<Ret, Error> {
+				self.check_context()?;
+
 				let mut method_name = CSharpNativeString::new(name);
 
 				let len = count_params!($($arg),+);
@@ -327,7 +414,7 @@ macro_rules! impl_type_fns {
 
                 CSharpNativeString::free(&mut method_name);
 
-				result
+				Ok(result)
 			}
 		}
 	};
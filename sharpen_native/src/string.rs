@@ -24,6 +24,13 @@ impl CSharpNativeString {
         res
     }
 
+    pub fn null() -> Self {
+        Self {
+            string: std::ptr::null(),
+            _is_disposed: Bool32(0),
+        }
+    }
+
     pub fn assign(&mut self, str: &str) {
         if !self.string.is_null() {
             Self::dealloc(self.string);
@@ -130,3 +137,53 @@ impl PartialEq for ScopedCSharpNativeString {
     }
 }
 impl Eq for ScopedCSharpNativeString {}
+
+/// Strings handed over to C#, which frees them with `Marshal.FreeCoTaskMem` (e.g the ones internal calls return)
+impl CSharpNativeString {
+    pub(crate) fn new_co_task_mem(str: &str) -> Self {
+        let string = widestring::WideCString::from_str_truncate(str);
+
+        unsafe {
+            let ptr = co_task_mem::alloc((string.len() + 1) * size_of::<PdChar>()) as *mut PdChar;
+            if !ptr.is_null() {
+                std::ptr::copy_nonoverlapping(string.as_ptr(), ptr, string.len() + 1);
+            }
+
+            Self {
+                string: ptr,
+                _is_disposed: Bool32(0),
+            }
+        }
+    }
+
+    pub(crate) fn free_co_task_mem(string: &mut Self) {
+        if string.string.is_null() {
+            return;
+        }
+
+        unsafe {
+            co_task_mem::free(string.string as *mut _);
+        }
+        string.string = std::ptr::null();
+    }
+}
+
+/// The allocator behind `Marshal.AllocCoTaskMem`, which is `malloc` outside of Windows
+mod co_task_mem {
+    #[cfg(windows)]
+    #[link(name = "ole32")]
+    unsafe extern "system" {
+        #[link_name = "CoTaskMemAlloc"]
+        pub fn alloc(size: usize) -> *mut std::ffi::c_void;
+        #[link_name = "CoTaskMemFree"]
+        pub fn free(ptr: *mut std::ffi::c_void);
+    }
+
+    #[cfg(not(windows))]
+    unsafe extern "C" {
+        #[link_name = "malloc"]
+        pub fn alloc(size: usize) -> *mut std::ffi::c_void;
+        #[link_name = "free"]
+        pub fn free(ptr: *mut std::ffi::c_void);
+    }
+}
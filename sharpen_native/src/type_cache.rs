@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::{TypeId, sharp_type::Type};
 
@@ -10,6 +13,7 @@ pub enum TypeCacheError {
 pub struct TypeCache {
     name_cache: HashMap<String, Arc<Type>>,
     id_cache: HashMap<TypeId, Arc<Type>>,
+    shared_types: HashSet<TypeId>,
 }
 
 impl TypeCache {
@@ -17,12 +21,25 @@ impl TypeCache {
         Self {
             name_cache: HashMap::new(),
             id_cache: HashMap::new(),
+            shared_types: HashSet::new(),
         }
     }
 
+    /// Types of an unloaded context aren't cached, and a type that knows its context isn't replaced by one that doesn't
     pub fn cache_type(&mut self, r#type: Arc<Type>) {
-        self.name_cache
-            .insert(r#type.get_full_name(), r#type.clone());
+        let replaces_owned_type = self
+            .id_cache
+            .get(&r#type.get_type_id())
+            .is_some_and(|cached| cached.context.is_some() && r#type.context.is_none());
+        if replaces_owned_type {
+            return;
+        }
+
+        let Ok(full_name) = r#type.get_full_name() else {
+            return;
+        };
+
+        self.name_cache.insert(full_name, r#type.clone());
         self.id_cache.insert(r#type.get_type_id(), r#type.clone());
     }
 
@@ -40,8 +57,29 @@ impl TypeCache {
             .ok_or(TypeCacheError::TypeNotFound)
     }
 
+    /// Whether the type comes from an assembly loaded with [`crate::host_instance::HostInstance::load_shared_assembly`],
+    /// meaning it has the same identity in every context
+    pub fn is_shared_type(&self, id: TypeId) -> bool {
+        self.shared_types.contains(&id)
+    }
+
+    pub(crate) fn mark_shared(&mut self, id: TypeId) {
+        self.shared_types.insert(id);
+    }
+
+    /// Clears every cached type except the shared ones, which stay valid until the host shuts down
     pub fn clear(&mut self) {
-        self.name_cache.clear();
-        self.id_cache.clear();
+        let shared_types = &self.shared_types;
+        self.name_cache
+            .retain(|_, r#type| shared_types.contains(&r#type.get_type_id()));
+        self.id_cache.retain(|id, _| shared_types.contains(id));
+    }
+
+    /// Drops the types owned by contexts that were unloaded, called when a context unloads
+    pub(crate) fn evict_unloaded(&mut self) {
+        let is_unloaded = |r#type: &Type| r#type.check_context().is_err();
+
+        self.name_cache.retain(|_, r#type| !is_unloaded(r#type));
+        self.id_cache.retain(|_, r#type| !is_unloaded(r#type));
     }
 }
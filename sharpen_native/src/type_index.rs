@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use crate::{TypeId, host_instance::HostInstance, string::CSharpNativeString};
+
+#[repr(C)]
+pub(crate) struct TypeIndexEntry {
+    type_id: TypeId,
+    namespace: CSharpNativeString,
+    base_types_offset: i32,
+    base_type_count: i32,
+    interfaces_offset: i32,
+    interface_count: i32,
+    attributes_offset: i32,
+    attribute_count: i32,
+}
+
+/// Lookup tables for the types of a single assembly, the values are indices into the assembly's type list
+#[derive(Default)]
+pub(crate) struct TypeIndex {
+    pub(crate) type_ids: Vec<TypeId>,
+    namespaces: HashMap<String, Vec<usize>>,
+    subclasses: HashMap<TypeId, Vec<usize>>,
+    implementors: HashMap<TypeId, Vec<usize>>,
+    attributes: HashMap<TypeId, Vec<usize>>,
+}
+
+impl TypeIndex {
+    /// Builds the index for every type in the assembly, C# builds it once on the count call and copies it out on the fill call
+    pub(crate) fn build(assembly_id: i32, host: &HostInstance) -> Self {
+        let get_assembly_type_index = &host.managed_functions().get_assembly_type_index;
+
+        let mut entry_count = 0i32;
+        let mut related_type_count = 0i32;
+        get_assembly_type_index(
+            assembly_id,
+            std::ptr::null_mut(),
+            0,
+            &mut entry_count,
+            std::ptr::null_mut(),
+            0,
+            &mut related_type_count,
+        );
+
+        let mut entries = Vec::<TypeIndexEntry>::with_capacity(entry_count as usize);
+        let mut related_types = Vec::<TypeId>::with_capacity(related_type_count as usize);
+        get_assembly_type_index(
+            assembly_id,
+            entries.as_mut_ptr(),
+            entries.capacity() as i32,
+            &mut entry_count,
+            related_types.as_mut_ptr(),
+            related_types.capacity() as i32,
+            &mut related_type_count,
+        );
+        unsafe {
+            entries.set_len((entry_count as usize).min(entries.capacity()));
+            related_types.set_len((related_type_count as usize).min(related_types.capacity()));
+        }
+
+        let mut index = Self::default();
+        for (i, entry) in entries.iter_mut().enumerate() {
+            index.type_ids.push(entry.type_id);
+
+            index
+                .namespaces
+                .entry(entry.namespace.to_string())
+                .or_default()
+                .push(i);
+            CSharpNativeString::free(&mut entry.namespace);
+
+            // NOTE: Only out of range if another build took the cached index and C# built a different one for the fill call
+            let related = |offset: i32, count: i32| {
+                related_types
+                    .get(offset as usize..(offset + count) as usize)
+                    .unwrap_or_default()
+            };
+            for base_type in related(entry.base_types_offset, entry.base_type_count) {
+                index.subclasses.entry(*base_type).or_default().push(i);
+            }
+            for interface in related(entry.interfaces_offset, entry.interface_count) {
+                index.implementors.entry(*interface).or_default().push(i);
+            }
+            for attribute in related(entry.attributes_offset, entry.attribute_count) {
+                index.attributes.entry(*attribute).or_default().push(i);
+            }
+        }
+
+        index
+    }
+
+    pub(crate) fn in_namespace(&self, namespace: &str) -> &[usize] {
+        self.namespaces.get(namespace).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn subclasses_of(&self, type_id: TypeId) -> &[usize] {
+        self.subclasses.get(&type_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn implementors_of(&self, type_id: TypeId) -> &[usize] {
+        self.implementors.get(&type_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn with_attribute(&self, type_id: TypeId) -> &[usize] {
+        self.attributes.get(&type_id).map_or(&[], Vec::as_slice)
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    ffi::c_void,
+    panic::AssertUnwindSafe,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{
+    Bool32,
+    host_instance::panic_message,
+    string::{CSharpNativeString, ScopedCSharpNativeString},
+};
+
+pub type AssemblyVerifierFn = Box<dyn Fn(&[u8]) -> Result<(), String> + Send + Sync>;
+
+/// Checks every assembly before it's loaded into an [`crate::assembly::AssemblyLoadContext`], including dependencies.
+/// An assembly has to pass every check that is set, an empty list skips that check.
+#[derive(Default)]
+pub struct VerificationPolicy {
+    /// Hex encoded SHA-256 hashes of the assemblies that may be loaded
+    pub allowed_sha256: Vec<String>,
+    /// Hex encoded public key tokens (e.g `b77a5c561934e089`), the assembly has to be strong-name signed with one of them.
+    /// NOTE: .NET doesn't validate strong-name signatures, so this only checks the key an assembly claims to be signed with.
+    pub required_public_key_tokens: Vec<String>,
+    /// Called with the raw bytes of the assembly, returning `Err` rejects it with the given reason
+    pub verifier: Option<AssemblyVerifierFn>,
+}
+
+pub(crate) type AssemblyVerifierCallbackFn =
+    extern "system" fn(*mut c_void, *const u8, i64, *mut CSharpNativeString) -> Bool32;
+
+/// Handed to C# as user data, so it has to stay at a stable address (boxed) for as long as the context is alive
+#[derive(Default)]
+pub(crate) struct VerificationState {
+    pub(crate) verifier: Option<AssemblyVerifierFn>,
+    // NOTE: C# copies the reason out right away, so we only have to keep the last one alive
+    last_reason: Mutex<Option<ScopedCSharpNativeString>>,
+}
+
+pub(crate) extern "system" fn verify_assembly_callback(
+    user_data: *mut c_void,
+    assembly_data: *const u8,
+    assembly_data_length: i64,
+    out_reason: *mut CSharpNativeString,
+) -> Bool32 {
+    let state = unsafe { &*(user_data as *const VerificationState) };
+
+    let Some(verifier) = &state.verifier else {
+        return true.into();
+    };
+
+    let assembly =
+        unsafe { std::slice::from_raw_parts(assembly_data, assembly_data_length as usize) };
+    // NOTE: A panic can't unwind into C#, so a verifier that panics rejects the assembly instead
+    let reason = match std::panic::catch_unwind(AssertUnwindSafe(|| verifier(assembly))) {
+        Ok(Ok(())) => return true.into(),
+        Ok(Err(reason)) => reason,
+        Err(payload) => format!("The verifier panicked: {}", panic_message(payload.as_ref())),
+    };
+
+    let reason = ScopedCSharpNativeString::from_str(&reason);
+    unsafe {
+        out_reason.write(reason.inner());
+    }
+    *state
+        .last_reason
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(reason);
+
+    false.into()
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::{Read, Seek},
+    path::Path,
+    sync::Mutex,
+};
+
+use zip::{ZipArchive, result::ZipResult};
+
+/// A read-only file tree (e.g a pak or zip archive) that assemblies and their PDBs can be loaded from.
+/// See [`crate::assembly::AssemblyLoadContext::mount`].
+pub trait VirtualAssemblySource: Send + Sync {
+    /// The path of every file in the source, using `/` as the separator
+    fn entries(&self) -> Vec<String>;
+
+    /// Reads the whole file at `path`, `None` if it doesn't exist
+    fn read(&self, path: &str) -> Option<Vec<u8>>;
+}
+
+/// Reads `<name>.dll` (and `<name>.pdb` next to it, if present) from anywhere in `source`.
+/// File names are compared case-insensitively, like on Windows.
+pub(crate) fn find_assembly(
+    source: &dyn VirtualAssemblySource,
+    name: &str,
+) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+    let file_name = format!("{name}.dll");
+
+    let entry = source.entries().into_iter().find(|entry| {
+        entry
+            .rsplit('/')
+            .next()
+            .is_some_and(|entry_name| entry_name.eq_ignore_ascii_case(&file_name))
+    })?;
+
+    read_assembly(source, &entry)
+}
+
+/// Reads the assembly at `entry` together with the PDB that has the same path (but a `.pdb` extension)
+pub(crate) fn read_assembly(
+    source: &dyn VirtualAssemblySource,
+    entry: &str,
+) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+    let assembly = source.read(entry)?;
+
+    let symbols_entry = match entry.rsplit_once('.') {
+        Some((stem, _)) => format!("{stem}.pdb"),
+        None => format!("{entry}.pdb"),
+    };
+
+    Some((assembly, source.read(&symbols_entry)))
+}
+
+/// A [`VirtualAssemblySource`] backed by a zip archive
+pub struct ZipAssemblySource<R> {
+    // NOTE: Reading an entry needs a mutable archive, since it seeks the underlying reader
+    archive: Mutex<ZipArchive<R>>,
+}
+
+impl ZipAssemblySource<std::fs::File> {
+    pub fn open(path: &Path) -> ZipResult<Self> {
+        Self::new(std::fs::File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> ZipAssemblySource<R> {
+    pub fn new(reader: R) -> ZipResult<Self> {
+        Ok(Self {
+            archive: Mutex::new(ZipArchive::new(reader)?),
+        })
+    }
+}
+
+impl<R: Read + Seek + Send> VirtualAssemblySource for ZipAssemblySource<R> {
+    fn entries(&self) -> Vec<String> {
+        let archive = self
+            .archive
+            .lock()
+            .expect("ZipAssemblySource Mutex is poisoned");
+
+        archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn read(&self, path: &str) -> Option<Vec<u8>> {
+        let mut archive = self
+            .archive
+            .lock()
+            .expect("ZipAssemblySource Mutex is poisoned");
+
+        let mut file = archive.by_name(path).ok()?;
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut bytes).ok()?;
+
+        Some(bytes)
+    }
+}